@@ -0,0 +1,205 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text;
+use crate::theme::{Color, Paint};
+
+/// How the symbol of a cell is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Attrs {
+    pub bold: bool,
+    pub italic: bool,
+    pub invert: bool,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Attrs {
+    pub fn paint(paint: Paint) -> Attrs {
+        Attrs {
+            fg: paint.fg,
+            bg: paint.bg,
+            ..Attrs::default()
+        }
+    }
+}
+
+/// One column of a screen row: a grapheme, or an empty symbol right of a
+/// grapheme two columns wide.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub symbol: String,
+    pub attrs: Attrs,
+}
+
+impl Cell {
+    fn blank() -> Cell {
+        Cell {
+            symbol: " ".to_string(),
+            attrs: Attrs::default(),
+        }
+    }
+
+    fn is_continuation(&self) -> bool {
+        self.symbol.is_empty()
+    }
+}
+
+/// The screen drawn in memory, so only what changed since the last frame
+/// has to be sent to the terminal.
+pub struct Buffer {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Buffer {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::blank(); width as usize * height as usize],
+        }
+    }
+
+    /// Blanks every cell.
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::blank());
+    }
+
+    /// The cell at column `x` of row `y`, both counted from 1 like
+    /// `cursor::Goto`.
+    pub fn get(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[self.index(x, y)]
+    }
+
+    /// Writes `text` from column `x` of row `y`, both counted from 1 like
+    /// `cursor::Goto`. Whatever does not fit on the row is cut off.
+    pub fn put(&mut self, x: u16, y: u16, text: &str, attrs: Attrs) {
+        if y == 0 || y > self.height {
+            return;
+        }
+
+        let mut x = x.max(1);
+        for grapheme in text.graphemes(true) {
+            let width = text::width(grapheme) as u16;
+            if width == 0 {
+                continue;
+            }
+
+            if x + width - 1 > self.width {
+                // a wide grapheme in the last column is cut in half
+                if x <= self.width {
+                    self.set(x, y, " ", attrs);
+                }
+                break;
+            }
+
+            self.set(x, y, grapheme, attrs);
+            for x in x + 1..x + width {
+                self.set(x, y, "", attrs);
+            }
+
+            x += width;
+        }
+
+        // the right half of a wide grapheme that was just overwritten
+        if x <= self.width && self.get(x, y).is_continuation() {
+            let i = self.index(x, y);
+            self.cells[i] = Cell::blank();
+        }
+    }
+
+    /// Cells that differ from `previous`, left to right and top to
+    /// bottom, with their columns and rows. The right halves of wide
+    /// graphemes are left out since drawing the left half covers them.
+    pub fn changes<'a>(&'a self, previous: &'a Buffer) -> impl Iterator<Item = (u16, u16, &'a Cell)> {
+        let width = self.width;
+
+        self.cells.iter().enumerate()
+            .filter(move |&(i, cell)| !cell.is_continuation() && previous.cells.get(i) != Some(cell))
+            .map(move |(i, cell)| ((i % width as usize) as u16 + 1, (i / width as usize) as u16 + 1, cell))
+    }
+
+    fn set(&mut self, x: u16, y: u16, symbol: &str, attrs: Attrs) {
+        let i = self.index(x, y);
+
+        // the left half of a wide grapheme that is partly overwritten
+        if !symbol.is_empty() && self.cells[i].is_continuation() && x > 1 {
+            self.cells[i - 1] = Cell::blank();
+        }
+
+        self.cells[i] = Cell {
+            symbol: symbol.to_string(),
+            attrs,
+        };
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        (y as usize - 1) * self.width as usize + x as usize - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(buffer: &Buffer, y: u16) -> String {
+        (1..=buffer.width).map(|x| buffer.get(x, y).symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn puts_text_at_position() {
+        let mut buffer = Buffer::new(6, 2);
+        buffer.put(2, 2, "abc", Attrs::default());
+
+        assert_eq!(row(&buffer, 1), "      ");
+        assert_eq!(row(&buffer, 2), " abc  ");
+    }
+
+    #[test]
+    fn cuts_off_text_past_the_edge() {
+        let mut buffer = Buffer::new(4, 1);
+        buffer.put(3, 1, "abc", Attrs::default());
+        buffer.put(1, 2, "abc", Attrs::default());
+
+        assert_eq!(row(&buffer, 1), "  ab");
+    }
+
+    #[test]
+    fn wide_graphemes_take_two_cells() {
+        let mut buffer = Buffer::new(5, 1);
+        buffer.put(1, 1, "年a", Attrs::default());
+
+        assert_eq!(buffer.get(2, 1).symbol, "");
+        assert_eq!(row(&buffer, 1), "年a  ");
+    }
+
+    #[test]
+    fn overwriting_half_of_a_wide_grapheme_blanks_the_other_half() {
+        let mut buffer = Buffer::new(6, 1);
+        buffer.put(1, 1, "年年", Attrs::default());
+        buffer.put(2, 1, "ab", Attrs::default());
+
+        assert_eq!(row(&buffer, 1), " ab   ");
+    }
+
+    #[test]
+    fn cuts_wide_grapheme_in_last_column() {
+        let mut buffer = Buffer::new(3, 1);
+        buffer.put(2, 1, "a年", Attrs::default());
+
+        assert_eq!(row(&buffer, 1), " a ");
+    }
+
+    #[test]
+    fn changes_skip_unchanged_cells() {
+        let previous = Buffer::new(4, 2);
+        let mut buffer = Buffer::new(4, 2);
+        buffer.put(2, 2, "年", Attrs::default());
+        buffer.put(4, 1, "a", Attrs { bold: true, ..Attrs::default() });
+
+        let changes: Vec<(u16, u16, &str)> = buffer.changes(&previous).map(|(x, y, cell)| (x, y, cell.symbol.as_str())).collect();
+
+        assert_eq!(changes, vec![(4, 1, "a"), (2, 2, "年")]);
+    }
+}
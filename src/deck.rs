@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Most copies of one quiz in a deck, however much heavier it is than the
+/// lightest quiz.
+const MAX_COPIES: f64 = 100.0;
+
+/// How quizzes are picked so the same question does not keep coming back.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Policy {
+    /// Deal every quiz, as many times as its weight, before reshuffling.
+    #[default]
+    Deck,
+
+    /// Pick at random, skipping the last `--cooldown` quizzes.
+    Cooldown,
+
+    /// Pick at random, repeats allowed.
+    Random,
+}
+
+/// Picks quiz indices according to a `Policy`, favouring quizzes with a
+/// higher weight. Lives for the whole session, so it is not affected by
+/// resetting the game.
+pub struct Deck {
+    policy: Policy,
+    cooldown: usize,
+    pending: Vec<usize>,
+    recent: VecDeque<usize>,
+}
+
+impl Deck {
+    pub fn new(policy: Policy, cooldown: usize) -> Deck {
+        Deck {
+            policy,
+            cooldown,
+            pending: Vec::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Picks the next quiz, `weights` has one entry per quiz and at least
+    /// one of them must be positive.
+    pub fn draw(&mut self, weights: &[f64], rng: &mut impl Rng) -> usize {
+        let id = match self.policy {
+            Policy::Deck => self.deal(weights, rng),
+            Policy::Cooldown => self.pick_cold(weights, rng),
+            Policy::Random => pick(weights, (0..weights.len()).collect(), rng),
+        };
+
+        self.recent.push_back(id);
+        if self.recent.len() > self.cooldown.max(1) {
+            self.recent.pop_front();
+        }
+
+        id
+    }
+
+    /// Deals from a deck where every quiz has as many copies as its weight
+    /// relative to the lightest quiz.
+    fn deal(&mut self, weights: &[f64], rng: &mut impl Rng) -> usize {
+        if self.pending.is_empty() {
+            let lightest = weights.iter().copied().filter(|&weight| weight > 0.0).fold(f64::MAX, f64::min);
+
+            for (id, &weight) in weights.iter().enumerate() {
+                let copies = if weight > 0.0 { (weight / lightest).round().clamp(1.0, MAX_COPIES) } else { 0.0 };
+                self.pending.extend(std::iter::repeat_n(id, copies as usize));
+            }
+
+            self.pending.shuffle(rng);
+        }
+
+        // do not deal the card that was just dealt if there is another one
+        let last = self.recent.back().copied();
+        if self.pending.last().copied() == last {
+            if let Some(i) = self.pending.iter().position(|&id| Some(id) != last) {
+                let end = self.pending.len() - 1;
+                self.pending.swap(i, end);
+            }
+        }
+
+        self.pending.pop().unwrap()
+    }
+
+    fn pick_cold(&mut self, weights: &[f64], rng: &mut impl Rng) -> usize {
+        // keep at least one quiz available
+        let available = weights.iter().filter(|&&weight| weight > 0.0).count();
+        let cooldown = self.cooldown.min(available - 1);
+        let skip = self.recent.len().saturating_sub(cooldown);
+        let recent: Vec<usize> = self.recent.iter().skip(skip).copied().collect();
+
+        let candidates = (0..weights.len()).filter(|id| !recent.contains(id)).collect();
+        pick(weights, candidates, rng)
+    }
+}
+
+fn pick(weights: &[f64], candidates: Vec<usize>, rng: &mut impl Rng) -> usize {
+    *candidates.choose_weighted(rng, |&id| weights[id]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    fn draws(deck: &mut Deck, weights: &[f64], count: usize) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(2018);
+        (0..count).map(|_| deck.draw(weights, &mut rng)).collect()
+    }
+
+    #[test]
+    fn deck_deals_every_quiz_before_repeating() {
+        let ids = draws(&mut Deck::new(Policy::Deck, 0), &[1.0; 5], 15);
+
+        for round in ids.chunks(5) {
+            let mut round = round.to_vec();
+            round.sort();
+            assert_eq!(round, vec![0, 1, 2, 3, 4]);
+        }
+
+        assert!(ids.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn deck_has_copies_by_weight_up_to_a_limit() {
+        let ids = draws(&mut Deck::new(Policy::Deck, 0), &[1.0, 3.0], 4);
+        assert_eq!(ids.iter().filter(|&&id| id == 1).count(), 3);
+
+        let ids = draws(&mut Deck::new(Policy::Deck, 0), &[0.001, 1.0], MAX_COPIES as usize + 1);
+        assert_eq!(ids.iter().filter(|&&id| id == 0).count(), 1);
+    }
+
+    #[test]
+    fn cooldown_skips_recent_quizzes() {
+        let ids = draws(&mut Deck::new(Policy::Cooldown, 3), &[1.0; 5], 100);
+
+        for (i, id) in ids.iter().enumerate() {
+            assert!(!ids[i.saturating_sub(3)..i].contains(id), "{} repeated within the cooldown at {}", id, i);
+        }
+    }
+
+    #[test]
+    fn cooldown_longer_than_the_pack_still_picks() {
+        let ids = draws(&mut Deck::new(Policy::Cooldown, 5), &[1.0, 1.0, 0.0], 10);
+
+        // only the last quiz is skipped, so the two take turns
+        assert!(ids.windows(2).all(|pair| pair[0] != pair[1] && pair[0] != 2));
+    }
+
+    #[test]
+    fn zero_weight_is_never_picked() {
+        for policy in [Policy::Deck, Policy::Cooldown, Policy::Random] {
+            let ids = draws(&mut Deck::new(policy, 1), &[1.0, 0.0, 2.0], 100);
+            assert!(!ids.contains(&1), "{:?} picked a quiz with no weight", policy);
+        }
+    }
+}
@@ -0,0 +1,660 @@
+use std::fs;
+use std::io::{Read, Write, Result};
+use std::path::{Path, PathBuf};
+use std::{thread, time};
+
+use rand::thread_rng;
+use termion::{clear, cursor, style};
+
+use crate::import::Format;
+use crate::layout::{LAYOUT_QUIZ_HEIGHT, LAYOUT_QUIZ_WIDTH};
+use crate::locale::{self, Strings};
+use crate::panel::{draw_quiz_panel, CodeStyle, CurrentQuiz};
+use crate::quiz::{self, Kind, Pack, Quiz, Text};
+use crate::terminal::{into_screen, Screen, ARROW_DOWN, ARROW_LEFT, ARROW_RIGHT, ARROW_UP, BACKSPACE, PAGE_DOWN, PAGE_UP};
+use crate::text::{self, pad};
+use crate::{lint, markup};
+
+/// Width of the quiz list and the field list.
+const LIST_WIDTH: u16 = 44;
+
+/// Rows above the lists, for the title.
+const HEADER_HEIGHT: u16 = 2;
+
+/// Rows below the lists, for the status line and key help.
+const FOOTER_HEIGHT: u16 = 3;
+
+/// Separates the items of list fields while editing them. A `|` inside
+/// an item is typed as `\|`.
+const LIST_SEPARATOR: char = '|';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Kind,
+    Question,
+    Code,
+    Answer,
+    Answers,
+    WrongAnswers,
+    Alternatives,
+    Weight,
+}
+
+const FIELDS: [Field; 8] = [
+    Field::Kind,
+    Field::Question,
+    Field::Code,
+    Field::Answer,
+    Field::Answers,
+    Field::WrongAnswers,
+    Field::Alternatives,
+    Field::Weight,
+];
+
+impl Field {
+    /// Named like the YAML key.
+    fn name(self) -> &'static str {
+        match self {
+            Field::Kind => "kind",
+            Field::Question => "question",
+            Field::Code => "code",
+            Field::Answer => "answer",
+            Field::Answers => "answers",
+            Field::WrongAnswers => "wrong_answers",
+            Field::Alternatives => "alternatives",
+            Field::Weight => "weight",
+        }
+    }
+}
+
+enum View {
+    List,
+    Fields { field: usize },
+    Input { field: usize, input: String },
+    ConfirmDelete,
+    ConfirmQuit,
+}
+
+/// Opens a YAML quiz pack for editing, or an empty one if the file does
+/// not exist yet. Also tells whether the file is a plain list of quizzes,
+/// so it is saved the same way.
+pub fn open(path: &Path) -> std::result::Result<(Pack, bool), String> {
+    if Format::from_path(path) != Some(Format::Yaml) {
+        return Err("only YAML packs can be edited, convert others with `tequiz convert` first".to_string());
+    }
+
+    if !path.exists() {
+        return Ok((Pack::new(Vec::new()), true));
+    }
+
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let value: serde_yaml::Value = serde_yaml::from_str(&source).map_err(|err| err.to_string())?;
+    let pack = Pack::parse(&source).map_err(|err| err.to_string())?;
+
+    Ok((pack, value.is_sequence()))
+}
+
+/// Lists, adds, changes, deletes and reorders the quizzes of a pack,
+/// previewing each one like the quiz panel of the game shows it.
+pub struct Editor<R, W: Write> {
+    stdin: R,
+    stdout: W,
+    term_width: u16,
+    term_height: u16,
+
+    path: PathBuf,
+    pack: Pack,
+    as_list: bool,
+    language: String,
+    strings: &'static Strings,
+    code_style: CodeStyle,
+
+    view: View,
+    selected: usize,
+    top: usize,
+    preview: Option<CurrentQuiz>,
+    status: String,
+    modified: bool,
+}
+
+impl<R: Read, W: Write> Editor<R, W> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(stdin: R, stdout: W, term_width: u16, term_height: u16, path: PathBuf, pack: Pack, as_list: bool, language: String, code_style: CodeStyle) -> Editor<R, Screen<W>> {
+        let strings = locale::strings(&language);
+
+        Editor {
+            stdin,
+            stdout: into_screen(stdout),
+            term_width,
+            term_height,
+            path,
+            pack,
+            as_list,
+            language,
+            strings,
+            code_style,
+            view: View::List,
+            selected: 0,
+            top: 0,
+            preview: None,
+            status: String::new(),
+            modified: false,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        write!(self.stdout, "{}", cursor::Hide)?;
+        self.draw()?;
+
+        // escape sequences such as PageUp take 4 bytes
+        let mut b: [u8; 4] = [0; 4];
+        loop {
+            thread::sleep(time::Duration::from_millis(50));
+
+            if !matches!(self.stdin.read(&mut b), Ok(len) if len > 0) {
+                continue;
+            }
+
+            self.status.clear();
+            if !self.handle(&b) {
+                break;
+            }
+
+            b = [0; 4];
+            self.draw()?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles one key press. Returns false once the editor should close.
+    fn handle(&mut self, b: &[u8; 4]) -> bool {
+        let key = (b[0], b[1], b[2]);
+
+        match &mut self.view {
+            View::List => match key {
+                (b'q', 0, 0) | (b'\x1b', 0, 0) if self.modified => {
+                    self.status = format!("{}, s: {}, q: {}", self.strings.unsaved, self.strings.save, self.strings.quit_anyway);
+                    self.view = View::ConfirmQuit;
+                }
+                (b'q', 0, 0) | (b'\x1b', 0, 0) => return false,
+
+                (b'k', 0, 0) | ARROW_UP => self.select(self.selected.saturating_sub(1)),
+                (b'j', 0, 0) | ARROW_DOWN => self.select(self.selected + 1),
+                (b'K', 0, 0) if self.selected > 0 => {
+                    self.pack.quizzes.swap(self.selected, self.selected - 1);
+                    self.changed();
+                    self.select(self.selected - 1);
+                }
+                (b'J', 0, 0) if self.selected + 1 < self.pack.quizzes.len() => {
+                    self.pack.quizzes.swap(self.selected, self.selected + 1);
+                    self.changed();
+                    self.select(self.selected + 1);
+                }
+
+                (b'a', 0, 0) => {
+                    let at = (self.selected + 1).min(self.pack.quizzes.len());
+                    self.pack.quizzes.insert(at, Quiz::new(self.strings.new_question.to_string(), String::new()));
+                    self.changed();
+                    self.select(at);
+                    self.view = View::Fields { field: 1 };
+                }
+                (b'e', 0, 0) | (b'\r', 0, 0) if !self.pack.quizzes.is_empty() => self.view = View::Fields { field: 0 },
+                (b'd', 0, 0) if !self.pack.quizzes.is_empty() => {
+                    self.status = format!("{} y: {}, {}: {}", self.strings.delete_quiz, self.strings.yes, self.strings.any_other_key, self.strings.no);
+                    self.view = View::ConfirmDelete;
+                }
+                (b's', 0, 0) => self.save(),
+
+                PAGE_UP => self.scroll_preview(-1),
+                PAGE_DOWN => self.scroll_preview(1),
+
+                _ => (),
+            },
+
+            View::Fields { field } => match key {
+                (b'\x1b', 0, 0) | (b'q', 0, 0) => self.view = View::List,
+
+                (b'k', 0, 0) | ARROW_UP => *field = field.saturating_sub(1),
+                (b'j', 0, 0) | ARROW_DOWN => *field = (*field + 1).min(FIELDS.len() - 1),
+
+                // kinds are picked rather than typed
+                (b'h', 0, 0) | ARROW_LEFT if FIELDS[*field] == Field::Kind => self.cycle_kind(-1),
+                (b'l', 0, 0) | ARROW_RIGHT | (b'\r', 0, 0) if FIELDS[*field] == Field::Kind => self.cycle_kind(1),
+
+                (b'e', 0, 0) | (b'\r', 0, 0) if FIELDS[*field] != Field::Kind => {
+                    let field = *field;
+                    let input = field_value(&self.pack.quizzes[self.selected], FIELDS[field], &self.language);
+                    self.view = View::Input { field, input };
+                }
+                (b's', 0, 0) => self.save(),
+
+                PAGE_UP => self.scroll_preview(-1),
+                PAGE_DOWN => self.scroll_preview(1),
+
+                _ => (),
+            },
+
+            View::Input { field, input } => match key {
+                (b'\x1b', 0, 0) => self.view = View::Fields { field: *field },
+                (b'\r', 0, 0) => {
+                    let field = *field;
+                    let input = std::mem::take(input);
+                    self.view = View::Fields { field };
+
+                    match set_field(&mut self.pack.quizzes[self.selected], FIELDS[field], &input, &self.language) {
+                        Ok(()) => self.changed(),
+                        Err(err) => self.status = err,
+                    }
+                }
+                (BACKSPACE, 0, 0) => {
+                    input.pop();
+                }
+                _ if b[0] != b'\x1b' => {
+                    let len = b.iter().position(|&b| b == 0).unwrap_or(b.len());
+                    if let Ok(text) = std::str::from_utf8(&b[..len]) {
+                        input.extend(text.chars().filter(|c| !c.is_control()));
+                    }
+                }
+                _ => (),
+            },
+
+            View::ConfirmDelete => {
+                if key == (b'y', 0, 0) {
+                    self.pack.quizzes.remove(self.selected);
+                    self.changed();
+                    self.select(self.selected);
+                }
+
+                self.view = View::List;
+            }
+
+            View::ConfirmQuit => match key {
+                (b'q', 0, 0) => return false,
+                (b's', 0, 0) => {
+                    self.save();
+                    if !self.modified {
+                        return false;
+                    }
+                    self.view = View::List;
+                }
+                _ => self.view = View::List,
+            },
+        }
+
+        true
+    }
+
+    fn select(&mut self, selected: usize) {
+        self.selected = selected.min(self.pack.quizzes.len().saturating_sub(1));
+        self.preview = None;
+
+        // keep the selected quiz in view
+        let rows = self.list_height() as usize;
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+    }
+
+    fn changed(&mut self) {
+        self.modified = true;
+        self.preview = None;
+    }
+
+    fn cycle_kind(&mut self, step: isize) {
+        const KINDS: [Kind; 4] = [Kind::Choice, Kind::TrueFalse, Kind::MultiSelect, Kind::FreeText];
+
+        let quiz = &mut self.pack.quizzes[self.selected];
+        let i = KINDS.iter().position(|&kind| kind == quiz.kind).unwrap_or(0) as isize;
+        quiz.kind = KINDS[(i + step).rem_euclid(KINDS.len() as isize) as usize];
+
+        self.changed();
+    }
+
+    fn scroll_preview(&mut self, pages: i16) {
+        if let Some(preview) = &mut self.preview {
+            preview.scroll_by(pages, LAYOUT_QUIZ_WIDTH - 4, LAYOUT_QUIZ_HEIGHT);
+        }
+    }
+
+    /// Writes the pack back in the order shown, as a plain list of quizzes
+    /// if it was one.
+    fn save(&mut self) {
+        let yaml = if self.as_list && self.pack.name.is_empty() && self.pack.weight == 1.0 {
+            serde_yaml::to_string(&self.pack.quizzes)
+        } else {
+            serde_yaml::to_string(&self.pack)
+        };
+
+        let saved = yaml.map_err(|err| err.to_string())
+            .and_then(|yaml| fs::write(&self.path, yaml).map_err(|err| err.to_string()));
+
+        match saved {
+            Ok(()) => {
+                self.modified = false;
+                self.status = format!("{} {}", self.strings.saved_to, self.path.display());
+            }
+            Err(err) => self.status = format!("{}: {}", self.strings.cannot_save, err),
+        }
+    }
+
+    fn list_height(&self) -> u16 {
+        self.term_height.saturating_sub(HEADER_HEIGHT + FOOTER_HEIGHT).max(1)
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        write!(self.stdout, "{}", clear::All)?;
+
+        let title = if self.modified {
+            format!("{} ({})", self.path.display(), self.strings.modified)
+        } else {
+            self.path.display().to_string()
+        };
+        write!(self.stdout, "{}{}{}{}", cursor::Goto(1, 1), style::Bold, title, style::Reset)?;
+
+        match self.view {
+            View::List | View::ConfirmDelete | View::ConfirmQuit => self.draw_list()?,
+            View::Fields { field } | View::Input { field, .. } => self.draw_fields(field)?,
+        }
+
+        if let Some(quiz) = self.pack.quizzes.get(self.selected) {
+            if self.preview.is_none() {
+                self.preview = Some(CurrentQuiz::from_quiz(&quiz.localize(&self.language), self.strings, &mut thread_rng()));
+            }
+
+            let problems = lint::lint_quiz(quiz);
+            self.draw_preview(&problems)?;
+        }
+
+        let strings = self.strings;
+        let (status, help) = match &self.view {
+            View::List => (self.status.clone(), format!(
+                "↑↓: {}  J/K: {}  a: {}  e: {}  d: {}  s: {}  q: {}",
+                strings.select, strings.move_quiz, strings.add, strings.edit, strings.delete, strings.save, strings.quit,
+            )),
+            View::Fields { .. } => (self.status.clone(), format!("↑↓: {}  ⏎: {}  s: {}  Esc: {}", strings.select, strings.change, strings.save, strings.back)),
+            View::Input { field, input } => {
+                let prompt = format!("{}: ", FIELDS[*field].name());
+                let room = (self.term_width as usize).saturating_sub(text::width(&prompt) + 1);
+                let help = format!("⏎: {}  Esc: {}  \\n: {}  \\|: {}  \\\\: \\", strings.done, strings.cancel, strings.new_line, strings.separator_in_item);
+                (format!("{}{}_", prompt, tail(input, room)), help)
+            }
+            View::ConfirmDelete | View::ConfirmQuit => (self.status.clone(), String::new()),
+        };
+
+        let width = self.term_width as usize;
+        write!(self.stdout, "{}{}", cursor::Goto(1, self.term_height.saturating_sub(1)), text::truncate(&status, width))?;
+        write!(self.stdout, "{}{}", cursor::Goto(1, self.term_height), text::truncate(&help, width))?;
+
+        self.stdout.flush()
+    }
+
+    fn draw_list(&mut self) -> Result<()> {
+        if self.pack.quizzes.is_empty() {
+            return write!(self.stdout, "{}{}, a: {}", cursor::Goto(1, HEADER_HEIGHT + 1), self.strings.no_quizzes, self.strings.add_one);
+        }
+
+        let rows = self.list_height() as usize;
+        for (row, i) in (self.top..self.pack.quizzes.len()).take(rows).enumerate() {
+            let question = markup::plain(&self.pack.quizzes[i].question);
+            let item = format!("{:>3}. {}", i + 1, question);
+            self.draw_item(row as u16, &item, i == self.selected)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_fields(&mut self, selected: usize) -> Result<()> {
+        for (row, &field) in FIELDS.iter().enumerate() {
+            let value = field_value(&self.pack.quizzes[self.selected], field, &self.language);
+            let item = format!("{:>13}: {}", field.name(), value);
+            self.draw_item(row as u16, &item, row == selected)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_item(&mut self, row: u16, item: &str, selected: bool) -> Result<()> {
+        let item = pad(&text::truncate(item, LIST_WIDTH as usize), LIST_WIDTH as usize);
+        write!(self.stdout, "{}", cursor::Goto(1, HEADER_HEIGHT + 1 + row))?;
+
+        if selected {
+            write!(self.stdout, "{}{}{}", style::Invert, item, style::Reset)
+        } else {
+            write!(self.stdout, "{}", item)
+        }
+    }
+
+    /// Draws the selected quiz exactly as wide as the quiz panel, with
+    /// what `tequiz lint` would say about it below.
+    fn draw_preview(&mut self, problems: &[String]) -> Result<()> {
+        let x = LIST_WIDTH + 4;
+        let width = LAYOUT_QUIZ_WIDTH - 4;
+
+        write!(self.stdout, "{}╭{}╮", cursor::Goto(x - 2, HEADER_HEIGHT + 1), "─".repeat(width as usize + 2))?;
+        for y in HEADER_HEIGHT + 2..HEADER_HEIGHT + 4 + LAYOUT_QUIZ_HEIGHT {
+            write!(self.stdout, "{}│{}│", cursor::Goto(x - 2, y), cursor::Goto(x + width + 1, y))?;
+        }
+        write!(self.stdout, "{}╰{}╯", cursor::Goto(x - 2, HEADER_HEIGHT + 4 + LAYOUT_QUIZ_HEIGHT), "─".repeat(width as usize + 2))?;
+
+        if let Some(preview) = &self.preview {
+            draw_quiz_panel(&mut self.stdout, preview, x, HEADER_HEIGHT + 3, width, LAYOUT_QUIZ_HEIGHT, self.code_style)?;
+        }
+
+        let problem_width = self.term_width.saturating_sub(x - 2).max(1);
+        let lines = problems.iter().flat_map(|problem| text::wrap(&[(problem.as_str(), ())], problem_width));
+
+        let first = HEADER_HEIGHT + 5 + LAYOUT_QUIZ_HEIGHT;
+        let last = self.term_height.saturating_sub(FOOTER_HEIGHT);
+        for (y, line) in (first..=last).zip(lines) {
+            let line: String = line.into_iter().map(|(text, _)| text).collect();
+            write!(self.stdout, "{}{}", cursor::Goto(x - 2, y), line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R, W: Write> Drop for Editor<R, W> {
+    fn drop(&mut self) {
+        write!(self.stdout, "{}{}", style::Reset, cursor::Show).unwrap();
+    }
+}
+
+/// The field as it is typed in: lists joined with ` | `, and newlines
+/// written as `\n`. See `escape`.
+fn field_value(quiz: &Quiz, field: Field, language: &str) -> String {
+    let list = |texts: &[Text]| {
+        let items: Vec<String> = texts.iter().map(|text| escape(text.get(language), &[LIST_SEPARATOR])).collect();
+        items.join(&format!(" {} ", LIST_SEPARATOR))
+    };
+
+    match field {
+        Field::Kind => match quiz.kind {
+            Kind::Choice => "◀ choice ▶",
+            Kind::TrueFalse => "◀ true_false ▶",
+            Kind::MultiSelect => "◀ multi_select ▶",
+            Kind::FreeText => "◀ free_text ▶",
+        }.to_string(),
+        Field::Question => quiz.question.get(language).to_string(),
+        Field::Code => escape(&quiz.code, &[]),
+        Field::Answer => quiz.answer.get(language).to_string(),
+        Field::Answers => list(&quiz.answers),
+        Field::WrongAnswers => list(&quiz.wrong_answers),
+        Field::Alternatives => list(&quiz.alternatives),
+        Field::Weight => quiz.weight.to_string(),
+    }
+}
+
+/// Sets the field from what was typed in. Only the `language` variant of
+/// translated texts is changed.
+fn set_field(quiz: &mut Quiz, field: Field, input: &str, language: &str) -> std::result::Result<(), String> {
+    let set_list = |texts: &mut Vec<Text>| {
+        let items: Vec<String> = unescape(input, Some(LIST_SEPARATOR)).iter()
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect();
+
+        *texts = set_items(std::mem::take(texts), items, language);
+    };
+
+    match field {
+        Field::Kind => (),
+        Field::Question => quiz.question.set(language, input.trim().to_string()),
+        Field::Code => {
+            quiz.code = unescape(input, None).concat();
+            if !quiz.code.is_empty() && !quiz.code.ends_with('\n') {
+                quiz.code.push('\n');
+            }
+        }
+        Field::Answer => quiz.answer.set(language, input.trim().to_string()),
+        Field::Answers => set_list(&mut quiz.answers),
+        Field::WrongAnswers => set_list(&mut quiz.wrong_answers),
+        Field::Alternatives => set_list(&mut quiz.alternatives),
+        Field::Weight => {
+            let weight = input.trim().parse().map_err(|_| format!("invalid weight {:?}", input.trim()))?;
+            quiz::validate_weight(weight)?;
+            quiz.weight = weight;
+        }
+    }
+
+    Ok(())
+}
+
+/// The items of a list field after editing. With as many items as before,
+/// each one is taken to be an edit of the one in its place. Otherwise items
+/// that did not change keep their translations wherever they moved, and
+/// the others are new.
+fn set_items(mut texts: Vec<Text>, items: Vec<String>, language: &str) -> Vec<Text> {
+    if texts.len() == items.len() {
+        for (text, item) in texts.iter_mut().zip(items) {
+            text.set(language, item);
+        }
+
+        return texts;
+    }
+
+    items.into_iter()
+        .map(|item| match texts.iter().position(|text| text.get(language) == item) {
+            Some(i) => texts.remove(i),
+            None => Text::from(item.as_str()),
+        })
+        .collect()
+}
+
+/// Writes `text` on one line: backslashes become `\\`, newlines `\n`, and
+/// each of `special` is marked with a backslash.
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if special.contains(&c) => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Reads back what `escape` wrote, split at each `separator` that is not
+/// escaped. A backslash before anything else is kept as it is.
+fn unescape(input: &str, separator: Option<char>) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        let item = items.last_mut().unwrap();
+
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => item.push('\n'),
+                Some(c) if c == '\\' || Some(c) == separator => item.push(c),
+                Some(c) => {
+                    item.push('\\');
+                    item.push(c);
+                }
+                None => item.push('\\'),
+            },
+            c if Some(c) == separator => items.push(String::new()),
+            c => item.push(c),
+        }
+    }
+
+    items
+}
+
+/// The end of `text` that fits in `width` columns.
+fn tail(text: &str, width: usize) -> &str {
+    let mut tail = text;
+    while text::width(tail) > width {
+        let mut chars = tail.chars();
+        chars.next();
+        tail = chars.as_str();
+    }
+
+    tail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_with_escapes_survives_editing() {
+        let mut quiz = Quiz::new("What does this print?".to_string(), "a".to_string());
+        quiz.code = "println!(\"a\\n\");\nlet path = \"C:\\\\\";\n".to_string();
+
+        let code = quiz.code.clone();
+        let input = field_value(&quiz, Field::Code, "en");
+        assert!(!input.contains('\n'));
+
+        set_field(&mut quiz, Field::Code, &input, "en").unwrap();
+        assert_eq!(quiz.code, code);
+    }
+
+    #[test]
+    fn list_items_may_contain_the_separator() {
+        let mut quiz = Quiz::new("Which is a boolean or?".to_string(), "a || b".to_string());
+        quiz.wrong_answers = vec![Text::from("a | b"), Text::from("a or b")];
+
+        let input = field_value(&quiz, Field::WrongAnswers, "en");
+        assert_eq!(input, "a \\| b | a or b");
+
+        set_field(&mut quiz, Field::WrongAnswers, &input, "en").unwrap();
+        assert_eq!(quiz.wrong_answers, vec![Text::from("a | b"), Text::from("a or b")]);
+    }
+
+    #[test]
+    fn removing_an_item_keeps_translations_with_their_items() {
+        let translated = |en: &str, sv: &str| Text::Translated([("en".to_string(), en.to_string()), ("sv".to_string(), sv.to_string())].into());
+        let one = translated("one", "ett");
+        let two = translated("two", "två");
+
+        let mut quiz = Quiz::new("Count".to_string(), "three".to_string());
+        quiz.wrong_answers = vec![one, two.clone()];
+
+        set_field(&mut quiz, Field::WrongAnswers, "two", "en").unwrap();
+        assert_eq!(quiz.wrong_answers, vec![two]);
+    }
+
+    #[test]
+    fn weights_are_validated() {
+        let mut quiz = Quiz::new("Who acquired iZettle?".to_string(), "PayPal".to_string());
+
+        for input in ["-1", "NaN", "inf", "often"] {
+            assert!(set_field(&mut quiz, Field::Weight, input, "en").is_err(), "{} was accepted", input);
+        }
+        assert_eq!(quiz.weight, 1.0);
+
+        set_field(&mut quiz, Field::Weight, " 0 ", "en").unwrap();
+        assert_eq!(quiz.weight, 0.0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::time::Duration;
-use rand::{self, rngs::ThreadRng, Rng, thread_rng, seq::SliceRandom};
+use rand::{rngs::StdRng, Rng, seq::SliceRandom};
 
 
 pub const WIDTH: u8 = 10;
@@ -15,11 +15,11 @@ pub struct Tetromino {
 impl Tetromino {
     pub fn get_cells(&self, position: u8, rotation: u8) -> [i16; 4] {
         let mut cells: [i16; 4] = [0; 4];
-        for i in 0..4 {
-            cells[i] = self.rotations[rotation as usize][i] + position as i16;
+        for (cell, offset) in cells.iter_mut().zip(self.rotations[rotation as usize]) {
+            *cell = offset + position as i16;
         }
 
-        return cells;
+        cells
     }
 }
 
@@ -110,13 +110,33 @@ const TETROMINOES: [Tetromino; TETROMINO_VARIANT] = [
     },
 ];
 
+/// What fills a cell of the grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Block {
+    #[default]
+    Empty,
+
+    /// Part of a tetromino, by its index in `TETROMINOES`.
+    Tetromino(usize),
+
+    /// Pushed up from below after a wrong answer.
+    Garbage,
+}
+
+impl Block {
+    pub fn is_filled(self) -> bool {
+        self != Block::Empty
+    }
+}
+
 const SCORE_MAP:[u32; 5] = [0, 4, 10, 30, 120];
 
 pub struct Grid {
-    pub cells: [bool; (WIDTH * HEIGHT) as usize],
+    pub cells: [Block; (WIDTH * HEIGHT) as usize],
 
     pub tetromino_id: Option<usize>,
     pub on_new_tetromino: bool,
+    pub on_lines_cleared: u8,
     pub position: u8,
     pub rotation: u8,
     pub interval: Duration,
@@ -125,17 +145,18 @@ pub struct Grid {
     pub score: u32,
     pub cleared: u32,
     pub level: u8,
-    pub rng: ThreadRng,
+    pub rng: StdRng,
     pub game_over: bool,
 
 }
 
 impl Grid {
-    pub fn new() -> Grid {
+    pub fn new(rng: StdRng) -> Grid {
         Grid {
-            cells: [false; (WIDTH * HEIGHT) as usize],
+            cells: [Block::Empty; (WIDTH * HEIGHT) as usize],
             tetromino_id: None,
             on_new_tetromino: false,
+            on_lines_cleared: 0,
             position: 0,
             rotation: 0,
             interval: INIT_INTERVAL,
@@ -144,7 +165,7 @@ impl Grid {
             score: 0,
             cleared: 0,
             level: 0,
-            rng: rand::thread_rng(),
+            rng,
             game_over: false,
         }
     }
@@ -154,10 +175,10 @@ impl Grid {
             return;
         }
 
-        self.timer = self.timer + interval;
+        self.timer += interval;
 
         if self.timer >= self.interval {
-            self.timer = self.timer - self.interval;
+            self.timer -= self.interval;
 
             match self.tetromino_id {
                 Some(_) => self.fall(true),
@@ -171,12 +192,12 @@ impl Grid {
             return;
         }
 
-        if let Some(_) = self.tetromino_id {
+        if self.tetromino_id.is_some() {
             if !self.move_if_can(self.position + WIDTH, self.rotation) {
                 self.clear();
                 self.next_tetromino();
             } else if due_to_gravity && self.gravity_bonus > 0 {
-                self.gravity_bonus = self.gravity_bonus - 1;
+                self.gravity_bonus -= 1;
             }
         }
     }
@@ -186,7 +207,7 @@ impl Grid {
             return;
         }
 
-        if let Some(_) = self.tetromino_id {
+        if self.tetromino_id.is_some() {
             self.move_if_can((self.position as i16 + offset) as u8, self.rotation);
         }
     }
@@ -196,7 +217,7 @@ impl Grid {
             return;
         }
 
-        if let Some(_) = self.tetromino_id {
+        if self.tetromino_id.is_some() {
             let next_rotation = (self.rotation + 1) % 4;
             if self.move_if_can(self.position, next_rotation) {
                 self.rotation = next_rotation;
@@ -208,7 +229,7 @@ impl Grid {
         let mut finished: [bool; HEIGHT as usize] = [true; HEIGHT as usize];
         for y in 0..HEIGHT {
             for x in 0..WIDTH {
-                if !self.cells[(y * WIDTH + x) as usize] {
+                if !self.cells[(y * WIDTH + x) as usize].is_filled() {
                     finished[y as usize] = false;
                     break;
                 }
@@ -224,7 +245,7 @@ impl Grid {
                 loop {
                     for x in 0..WIDTH {
                         if yy == 0 {
-                            self.cells[(yy * WIDTH + x) as usize] = false;
+                            self.cells[(yy * WIDTH + x) as usize] = Block::Empty;
                         } else {
                             self.cells[(yy * WIDTH + x) as usize] = self.cells[((yy - 1) * WIDTH + x) as usize];
                         }
@@ -234,17 +255,17 @@ impl Grid {
                         break;
                     }
 
-                    yy = yy - 1;
+                    yy -= 1;
                 }
 
-                cleared = cleared + 1;
+                cleared += 1;
             }
 
             if y == HEIGHT - 1 {
                 break;
             }
 
-            y = y + 1;
+            y += 1;
         }
 
         if cleared > 0 {
@@ -253,11 +274,13 @@ impl Grid {
     }
 
     fn update_score(&mut self, cleared: u8) {
-        self.cleared  = self.cleared + cleared as u32;
+        self.cleared += cleared as u32;
         let score = SCORE_MAP[cleared as usize] * self.gravity_bonus as u32 * (self.level as u32 + 1);
-        self.score = self.score + score;
+        self.score += score;
+        self.on_lines_cleared = cleared;
     }
 
+    #[allow(clippy::manual_is_multiple_of)]
     fn move_if_can(&mut self, new_position: u8, new_rotation: u8) -> bool {
         let tetromino_id = self.tetromino_id.unwrap();
         let current = TETROMINOES[tetromino_id].get_cells(self.position, self.rotation);
@@ -265,48 +288,48 @@ impl Grid {
 
         let mut can_move = true;
 
-        for i in 0..after.len() {
-            if after[i] < 0 {
+        for &cell in after.iter() {
+            if cell < 0 {
                 continue;
             }
 
-            if after[i] as usize >= self.cells.len() {
+            if cell as usize >= self.cells.len() {
                 can_move = false;
                 break;
             }
 
-            if self.cells[after[i] as usize] && !current.contains(&after[i]) {
+            if self.cells[cell as usize].is_filled() && !current.contains(&cell) {
                 can_move = false;
                 break;
             }
 
-            if after[i] as u8 % WIDTH == 0 && self.position % WIDTH >= WIDTH / 2 {
+            if cell as u8 % WIDTH == 0 && self.position % WIDTH >= WIDTH / 2 {
                 can_move = false;
                 break;
             }
 
-            if after[i] as u8 % WIDTH == WIDTH - 1 && self.position % WIDTH < WIDTH / 2 {
+            if cell as u8 % WIDTH == WIDTH - 1 && self.position % WIDTH < WIDTH / 2 {
                 can_move = false;
                 break;
             }
         }
 
         if can_move {
-            for i in 0..current.len() {
-                if current[i] >= 0 {
-                    self.cells[current[i] as usize] = false;
+            for &cell in current.iter() {
+                if cell >= 0 {
+                    self.cells[cell as usize] = Block::Empty;
                 }
             }
-            for i in 0..after.len() {
-                if after[i] >= 0 {
-                    self.cells[after[i] as usize] = true;
+            for &cell in after.iter() {
+                if cell >= 0 {
+                    self.cells[cell as usize] = Block::Tetromino(tetromino_id);
                 }
             }
             self.position = new_position;
             return true;
         }
 
-        return false;
+        false
     }
 
     fn next_tetromino(&mut self) {
@@ -318,8 +341,8 @@ impl Grid {
 
         let placement = TETROMINOES[n].get_cells(self.position, self.rotation);
 
-        for i in 0..placement.len() {
-            if self.cells[placement[i as usize] as usize] {
+        for &cell in placement.iter() {
+            if self.cells[cell as usize].is_filled() {
                 // cannot place new tetromino - GAME OVER!
                 self.game_over = true;
             }
@@ -343,7 +366,7 @@ impl Grid {
 
         // move all cells 1 row up
         for i in 0..self.cells.len() - WIDTH as usize {
-            let is_current = current.map_or(false, |tetromino| {
+            let is_current = current.is_some_and(|tetromino| {
                 tetromino.contains(&(i as i16)) || tetromino.contains(&((i + WIDTH as usize) as i16))
             });
 
@@ -353,26 +376,25 @@ impl Grid {
         }
 
         // populate random cells in last row - make sure it's not complete
-        let mut last_row: [bool; WIDTH as usize] = [false; WIDTH as usize];
+        let mut last_row: [Block; WIDTH as usize] = [Block::Empty; WIDTH as usize];
         let mut ratio = 100;
-        for i in 0..last_row.len() {
-            if thread_rng().gen_ratio(ratio, 100) {
-                last_row[i] = true;
-                ratio = ratio - 100 / WIDTH as u32;
+        for cell in last_row.iter_mut() {
+            if self.rng.gen_ratio(ratio, 100) {
+                *cell = Block::Garbage;
+                ratio -= 100 / WIDTH as u32;
             }
         }
 
-        last_row.shuffle(&mut thread_rng());
+        last_row.shuffle(&mut self.rng);
 
         let n = self.cells.len() - WIDTH as usize;
-        for i in 0..last_row.len() {
-            self.cells[n + i] = last_row[i];
-        }
+        self.cells[n..].copy_from_slice(&last_row);
     }
 
     pub fn reset(&mut self) {
         self.tetromino_id = None;
         self.on_new_tetromino = false;
+        self.on_lines_cleared = 0;
         self.interval = INIT_INTERVAL;
         self.timer = Duration::ZERO;
         self.gravity_bonus =  HEIGHT - 1;
@@ -381,10 +403,50 @@ impl Grid {
         self.level = 0;
         self.game_over = false;
 
-        self.cells = [false; (WIDTH * HEIGHT) as usize];
+        self.cells = [Block::Empty; (WIDTH * HEIGHT) as usize];
     }
 
-    pub fn reset_on_new_tetromino(&mut self) {
+    pub fn reset_events(&mut self) {
         self.on_new_tetromino = false;
+        self.on_lines_cleared = 0;
+    }
+
+    /// Rows from the bottom up to the highest settled cell, not counting
+    /// the falling tetromino.
+    pub fn stack_height(&self) -> u8 {
+        let current = self.tetromino_id.map(|id| {
+            TETROMINOES[id].get_cells(self.position, self.rotation)
+        });
+
+        for i in 0..self.cells.len() {
+            let is_current = current.is_some_and(|tetromino| tetromino.contains(&(i as i16)));
+
+            if self.cells[i].is_filled() && !is_current {
+                return HEIGHT - i as u8 / WIDTH;
+            }
+        }
+
+        0
+    }
+
+    /// Cells the falling tetromino would take if it dropped straight down
+    /// now.
+    pub fn ghost(&self) -> Option<[i16; 4]> {
+        let id = self.tetromino_id?;
+        let current = TETROMINOES[id].get_cells(self.position, self.rotation);
+
+        let mut position = self.position;
+        loop {
+            let below = TETROMINOES[id].get_cells(position + WIDTH, self.rotation);
+            let blocked = below.iter().any(|&cell| {
+                cell >= self.cells.len() as i16 || (cell >= 0 && self.cells[cell as usize].is_filled() && !current.contains(&cell))
+            });
+
+            if blocked {
+                return Some(TETROMINOES[id].get_cells(position, self.rotation));
+            }
+
+            position += WIDTH;
+        }
     }
 }
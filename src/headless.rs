@@ -0,0 +1,320 @@
+use std::io::Result;
+
+use crate::buffer::Buffer;
+use crate::layout::Layout;
+use crate::locale::Strings;
+use crate::render::{Renderer, Scale, View};
+use crate::theme::Theme;
+use crate::panel::CodeStyle;
+
+/// Draws the game into memory rather than on a terminal, so frames can be
+/// looked at as text.
+pub struct Headless {
+    layout: Layout,
+    buffer: Buffer,
+}
+
+impl Headless {
+    pub fn new(width: u16, height: u16, scale: Scale, theme: Theme, code_style: CodeStyle, strings: &'static Strings) -> Headless {
+        Headless {
+            layout: Layout::new(width, height, scale, theme, code_style, strings),
+            buffer: Buffer::new(width, height),
+        }
+    }
+
+    /// The last frame drawn, one line per row without trailing spaces.
+    pub fn frame(&self) -> String {
+        (1..=self.buffer.height)
+            .map(|y| {
+                let row: String = (1..=self.buffer.width).map(|x| self.buffer.get(x, y).symbol.as_str()).collect();
+                format!("{}\n", row.trim_end())
+            })
+            .collect()
+    }
+}
+
+impl Renderer for Headless {
+    fn fits(&self) -> bool {
+        self.layout.fits()
+    }
+
+    fn redraw(&mut self, view: &View) -> Result<()> {
+        self.draw(view)
+    }
+
+    fn draw(&mut self, view: &View) -> Result<()> {
+        self.buffer.clear();
+        self.layout.paint(view, &mut self.buffer);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::deck::{Deck, Policy};
+    use crate::locale;
+    use crate::theme;
+    use crate::quiz::{Kind, Mode, Quiz, Scoring, Text, ANSWER_KEYS};
+    use crate::trigger::{Strategy, Trigger};
+    use crate::grid::Block;
+    use crate::panel::CurrentQuiz;
+    use crate::terminal::PAGE_DOWN;
+    use crate::Game;
+
+    const SEED: u64 = 2018;
+
+    /// Ticks until the first piece drops in.
+    const FIRST_PIECE: usize = 20;
+
+    type TestGame = Game<io::Empty, Headless>;
+
+    fn game(width: u16, height: u16, quizzes: Vec<Quiz>, trigger: Trigger) -> TestGame {
+        styled_game(width, height, quizzes, trigger, Scale::new(2, 1), Theme::default())
+    }
+
+    fn styled_game(width: u16, height: u16, quizzes: Vec<Quiz>, trigger: Trigger, scale: Scale, theme: Theme) -> TestGame {
+        let strings = locale::strings("en");
+        let renderer = Headless::new(width, height, scale, theme, CodeStyle::Plain, strings);
+        let deck = Deck::new(Policy::Deck, 0);
+
+        let mut game = Game::new(io::empty(), renderer, quizzes, trigger, Mode::Lock, Scoring::AllOrNothing, deck, strings, StdRng::seed_from_u64(SEED));
+        game.redraw().unwrap();
+        game
+    }
+
+    fn no_quizzes() -> Trigger {
+        Trigger::new(Strategy::Piece, 0, 1, Duration::MAX)
+    }
+
+    fn quiz_every_piece() -> Trigger {
+        Trigger::new(Strategy::EveryN, 0, 1, Duration::MAX)
+    }
+
+    fn choice_quiz() -> Quiz {
+        let mut quiz = Quiz::new("What does this **Rust** print?".to_string(), "12".to_string());
+        quiz.code = "let v = vec![1, 2, 3];\nprintln!(\"{}\", v.len() * 4);\n".to_string();
+        quiz.wrong_answers = vec![Text::from("3"), Text::from("[1, 2, 3, 4]"), Text::from("It does not compile")];
+        quiz
+    }
+
+    fn free_text_quiz() -> Quiz {
+        let mut quiz = Quiz::new("Which year did PayPal acquire iZettle?".to_string(), "2018".to_string());
+        quiz.kind = Kind::FreeText;
+        quiz
+    }
+
+    fn key(text: &str) -> [u8; 4] {
+        let mut b = [0; 4];
+        b[..text.len()].copy_from_slice(text.as_bytes());
+        b
+    }
+
+    fn press(game: &mut TestGame, b: [u8; 4]) {
+        assert!(game.update(&b));
+        game.draw().unwrap();
+    }
+
+    fn wait(game: &mut TestGame, ticks: usize) {
+        for _ in 0..ticks {
+            press(game, [0; 4]);
+        }
+    }
+
+    /// Compares the current frame with `tests/golden/<name>.txt`, or
+    /// writes it there if `UPDATE_GOLDEN` is set.
+    fn assert_frame(game: &TestGame, name: &str) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", &format!("{}.txt", name)].iter().collect();
+        let frame = game.renderer.frame();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &frame).unwrap();
+            return;
+        }
+
+        let golden = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        assert!(frame == golden, "frame differs from {}, run with UPDATE_GOLDEN=1 to accept it\n{}", path.display(), frame);
+    }
+
+    #[test]
+    fn first_piece() {
+        let mut game = game(80, 24, vec![choice_quiz()], no_quizzes());
+        wait(&mut game, FIRST_PIECE + 1);
+        press(&mut game, key("l"));
+        press(&mut game, key("j"));
+
+        assert_frame(&game, "first_piece");
+    }
+
+    #[test]
+    fn quiz_panel_with_code() {
+        let mut game = game(80, 24, vec![choice_quiz()], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        assert_frame(&game, "quiz_panel_with_code");
+    }
+
+    #[test]
+    fn wrong_answer_adds_garbage() {
+        let mut game = game(80, 24, vec![choice_quiz()], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        let quiz = game.quiz.as_ref().unwrap();
+        let wrong = (0..quiz.answers.len()).find(|&id| !quiz.correct_answer_ids.contains(&(id as u8))).unwrap();
+        press(&mut game, [ANSWER_KEYS[wrong], 0, 0, 0]);
+
+        assert!(game.quiz.is_none());
+        assert_frame(&game, "wrong_answer_adds_garbage");
+    }
+
+    #[test]
+    fn partly_wrong_answers_add_up_to_garbage() {
+        let mut quiz = Quiz::new("Which of these are acquirers?".to_string(), String::new());
+        quiz.kind = Kind::MultiSelect;
+        quiz.answers = vec![Text::from("Amex"), Text::from("Elavon")];
+        quiz.wrong_answers = vec![Text::from("Datecs"), Text::from("SoftSpace"), Text::from("Hiso")];
+
+        let mut game = game(80, 24, vec![quiz], quiz_every_piece());
+        game.scoring = Scoring::Partial;
+        wait(&mut game, FIRST_PIECE + 2);
+
+        let garbage = |game: &TestGame| game.grid.cells.iter().filter(|&&cell| cell == Block::Garbage).count();
+
+        // ticking nothing gets the three wrong options right, 2/5 wrong
+        for answered in 1..=3 {
+            if game.quiz.is_none() {
+                game.quiz = Some(CurrentQuiz::from_quiz(&game.quizzes[0], game.strings, &mut game.rng));
+            }
+            press(&mut game, key("\r"));
+
+            assert_eq!(garbage(&game) > 0, answered == 3);
+        }
+    }
+
+    #[test]
+    fn enter_does_not_answer_a_choice() {
+        let mut game = game(80, 24, vec![choice_quiz()], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+        press(&mut game, key("\r"));
+
+        assert!(game.quiz.is_some());
+        assert_frame(&game, "quiz_panel_with_code");
+    }
+
+    #[test]
+    fn answers_past_nine_use_letters() {
+        let mut quiz = Quiz::new("Which number is twelve?".to_string(), "12".to_string());
+        quiz.wrong_answers = (1..=11).map(|n| Text::from(n.to_string().as_str())).collect();
+        assert_eq!(quiz.validate(), Ok(()));
+
+        let mut game = game(80, 24, vec![quiz], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        let quiz = game.quiz.as_ref().unwrap();
+        assert_eq!(quiz.answer_id(b'c'), Some(11));
+        assert_eq!(quiz.answer_id(b'h'), None);
+        assert_eq!(quiz.label(9), 'a');
+    }
+
+    #[test]
+    fn free_text_input() {
+        let mut game = game(80, 24, vec![free_text_quiz()], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        for c in ["2", "0", "1", "9"] {
+            press(&mut game, key(c));
+        }
+
+        assert_frame(&game, "free_text_input");
+    }
+
+    #[test]
+    fn scrolled_quiz() {
+        let mut quiz = choice_quiz();
+        quiz.code = (1..=12).map(|n| format!("let x{} = {};\n", n, n)).collect();
+
+        let mut game = game(80, 24, vec![quiz], quiz_every_piece());
+        wait(&mut game, FIRST_PIECE + 2);
+        press(&mut game, [PAGE_DOWN.0, PAGE_DOWN.1, PAGE_DOWN.2, b'~']);
+
+        assert_frame(&game, "scrolled_quiz");
+    }
+
+    #[test]
+    fn game_over() {
+        let mut game = game(80, 24, vec![choice_quiz()], no_quizzes());
+
+        for _ in 0..2000 {
+            if game.grid.game_over {
+                break;
+            }
+
+            press(&mut game, key("j"));
+        }
+
+        assert!(game.grid.game_over);
+        assert_frame(&game, "game_over");
+    }
+
+    #[test]
+    fn blocks_theme_with_ghost() {
+        let mut game = styled_game(80, 24, vec![choice_quiz()], no_quizzes(), Scale::new(2, 1), theme::load("blocks").unwrap());
+        wait(&mut game, FIRST_PIECE + 1);
+        press(&mut game, key("l"));
+        press(&mut game, key("j"));
+
+        assert_frame(&game, "blocks_theme_with_ghost");
+    }
+
+    #[test]
+    fn patterns_tell_pieces_apart() {
+        let mut theme = Theme::default();
+        theme.set_patterns();
+
+        let mut game = styled_game(80, 24, vec![choice_quiz()], no_quizzes(), Scale::new(2, 1), theme);
+        for (i, b) in "jjjjjjjjjjlllljjjjjjjjjjhhhhjjjjjjjjjjkjjjjjjjjjjlljjjjjjjjjjj".bytes().cycle().take(250).enumerate() {
+            if i % 2 == 0 {
+                wait(&mut game, 1);
+            }
+
+            press(&mut game, [b, 0, 0, 0]);
+        }
+
+        assert_frame(&game, "patterns_tell_pieces_apart");
+    }
+
+    #[test]
+    fn compact_scale() {
+        let mut game = styled_game(80, 24, vec![choice_quiz()], quiz_every_piece(), Scale::new(1, 1), Theme::default());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        assert_frame(&game, "compact_scale");
+    }
+
+    #[test]
+    fn large_scale() {
+        let mut game = styled_game(100, 46, vec![choice_quiz()], quiz_every_piece(), Scale::new(4, 2), Theme::default());
+        wait(&mut game, FIRST_PIECE + 2);
+
+        assert_frame(&game, "large_scale");
+    }
+
+    #[test]
+    fn too_small() {
+        let mut game = game(60, 16, vec![choice_quiz()], no_quizzes());
+        wait(&mut game, FIRST_PIECE + 1);
+
+        assert_eq!(game.grid.tetromino_id, None);
+        assert_frame(&game, "too_small");
+    }
+}
@@ -0,0 +1,547 @@
+use std::fs;
+use std::path::Path;
+
+use crate::quiz::{Kind, Pack, Quiz, Text};
+
+/// Keys of the header lines Anki writes at the top of an export, such as
+/// `#separator:tab`.
+const ANKI_HEADERS: [&str; 11] = [
+    "separator", "html", "tags", "columns", "notetype", "deck",
+    "notetype column", "deck column", "tags column", "guid column", "if matches",
+];
+
+/// Quiz pack file formats, told apart by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+    Csv,
+
+    /// Anki notes exported as plain text, one tab-separated card per line.
+    Anki,
+
+    /// Moodle GIFT question bank.
+    Gift,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "yaml" | "yml" => Some(Format::Yaml),
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "csv" => Some(Format::Csv),
+            "txt" | "tsv" => Some(Format::Anki),
+            "gift" => Some(Format::Gift),
+            _ => None,
+        }
+    }
+}
+
+/// Loads a quiz pack in the format its extension says. The pack is named
+/// after the file unless it names itself.
+pub fn load(path: &Path) -> Result<Pack, String> {
+    let format = Format::from_path(path).ok_or("unknown quiz pack format, expected .yaml, .json, .toml, .csv, .txt (Anki) or .gift")?;
+    load_as(path, format)
+}
+
+/// Loads a quiz pack in the given format whatever its extension.
+pub fn load_as(path: &Path, format: Format) -> Result<Pack, String> {
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+    let mut pack = parse(&source, format)?;
+    if pack.name.is_empty() {
+        pack.name = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+    }
+
+    Ok(pack)
+}
+
+pub fn parse(source: &str, format: Format) -> Result<Pack, String> {
+    match format {
+        Format::Yaml => Pack::parse(source).map_err(|err| err.to_string()),
+        Format::Json => parse_json(source),
+        Format::Toml => toml::from_str(source).map_err(|err| err.to_string()),
+        Format::Csv => parse_csv(source),
+        Format::Anki => parse_anki(source),
+        Format::Gift => parse_gift(source),
+    }
+}
+
+/// JSON packs, like YAML ones, are either a list of quizzes or a pack.
+fn parse_json(source: &str) -> Result<Pack, String> {
+    if source.trim_start().starts_with('[') {
+        let quizzes = serde_json::from_str(source).map_err(|err| err.to_string())?;
+        Ok(Pack::new(quizzes))
+    } else {
+        serde_json::from_str(source).map_err(|err| err.to_string())
+    }
+}
+
+/// CSV packs have a header row naming the columns: `question`, `answer`
+/// and any number of `wrong...` columns (`wrong1`, `wrong2`, ...), plus
+/// optional `kind` and `weight`. Empty cells are skipped. The answers of
+/// a multi-select quiz are separated by `|`.
+fn parse_csv(source: &str) -> Result<Pack, String> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(source.as_bytes());
+    let headers: Vec<String> = reader.headers()
+        .map_err(|err| err.to_string())?
+        .iter()
+        .map(|header| header.to_lowercase())
+        .collect();
+
+    let column = |name: &str| headers.iter().position(|header| header == name);
+    let question = column("question").ok_or("missing question column")?;
+    let answer = column("answer").ok_or("missing answer column")?;
+    let kind = column("kind");
+    let weight = column("weight");
+    let wrong: Vec<usize> = (0..headers.len()).filter(|&i| headers[i].starts_with("wrong")).collect();
+
+    let mut quizzes = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        let line = record.position().map_or(0, |position| position.line());
+        let cell = |i: usize| record.get(i).unwrap_or("").to_string();
+
+        let mut quiz = Quiz::new(cell(question), cell(answer));
+        quiz.wrong_answers = wrong.iter().map(|&i| cell(i)).filter(|answer| !answer.is_empty()).map(Text::from).collect();
+
+        if let Some(kind) = kind.map(cell).filter(|kind| !kind.is_empty()) {
+            quiz.kind = serde_yaml::from_str::<Kind>(&kind).map_err(|_| format!("line {}: unknown kind {:?}", line, kind))?;
+        }
+
+        if quiz.kind == Kind::MultiSelect {
+            quiz.answers = quiz.answer.split('|').map(|answer| Text::from(answer.trim())).collect();
+            quiz.answer = Text::default();
+        }
+
+        if let Some(weight) = weight.map(cell).filter(|weight| !weight.is_empty()) {
+            quiz.weight = weight.parse().map_err(|_| format!("line {}: invalid weight {:?}", line, weight))?;
+        }
+
+        quizzes.push(quiz);
+    }
+
+    Ok(Pack::new(quizzes))
+}
+
+/// Anki cards only have a front and a back, so each card becomes a
+/// free-text quiz answered with its back. Header lines such as
+/// `#html:true` and `#tags column:3` tell which columns to skip and
+/// whether to strip HTML. Cards may start with `#` too.
+fn parse_anki(source: &str) -> Result<Pack, String> {
+    let mut html = true;
+    let mut skipped = Vec::new();
+
+    let headers: Vec<(&str, &str)> = source.lines()
+        .map_while(|line| line.strip_prefix('#')?.split_once(':'))
+        .take_while(|(key, _)| ANKI_HEADERS.contains(key))
+        .collect();
+
+    for &(key, value) in headers.iter() {
+        match key {
+            "html" => html = value.trim() == "true",
+            "separator" if !matches!(value.trim(), "tab" | "Tab" | "\t") => {
+                return Err(format!("unsupported separator {:?}, export with tabs", value.trim()));
+            }
+            _ if key.ends_with(" column") => {
+                let column: usize = value.trim().parse().map_err(|_| format!("invalid header \"#{}:{}\"", key, value))?;
+                skipped.push(column.saturating_sub(1));
+            }
+            _ => (),
+        }
+    }
+
+    let cards = source.split_inclusive('\n').skip(headers.len()).collect::<String>();
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(cards.as_bytes());
+
+    let mut quizzes = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        let line = record.position().map_or(0, |position| position.line()) as usize + headers.len();
+
+        let mut fields = record.iter()
+            .enumerate()
+            .filter(|(i, _)| !skipped.contains(i))
+            .map(|(_, field)| if html { strip_html(field) } else { field.split_whitespace().collect::<Vec<_>>().join(" ") });
+
+        match (fields.next(), fields.next()) {
+            (Some(front), Some(back)) if !front.is_empty() && !back.is_empty() => {
+                let mut quiz = Quiz::new(front, back);
+                quiz.kind = Kind::FreeText;
+                quizzes.push(quiz);
+            }
+            _ => return Err(format!("line {}: expected a front and a back", line)),
+        }
+    }
+
+    Ok(Pack::new(quizzes))
+}
+
+/// Drops HTML tags, turning line breaks and blocks into spaces, decodes
+/// the common entities and collapses whitespace.
+fn strip_html(text: &str) -> String {
+    let mut plain = String::new();
+    let mut tag = None;
+
+    for c in text.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (Some(name), '>') => {
+                let name = name.trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+                if ["br", "div", "p", "li"].contains(&name.to_lowercase().as_str()) {
+                    plain.push(' ');
+                }
+                tag = None;
+            }
+            (Some(name), c) => name.push(c),
+            (None, c) => plain.push(c),
+        }
+    }
+
+    let plain = plain
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// GIFT questions are separated by blank lines and have their answers in
+/// braces: `{=right ~wrong}` is a choice, `{T}` true/false, `{=one =1}` a
+/// free-text answer with alternatives, and `~%50%` weights mark several
+/// right answers of a multi-select, any positive weight counting as
+/// right. Answers in the middle of the question leave a `____` blank.
+/// Questions without answers are descriptions and are skipped.
+fn parse_gift(source: &str) -> Result<Pack, String> {
+    let mut quizzes = Vec::new();
+
+    for (line, entry) in gift_entries(source) {
+        if let Some(quiz) = parse_gift_entry(&entry).map_err(|err| format!("line {}: {}", line, err))? {
+            quizzes.push(quiz);
+        }
+    }
+
+    Ok(Pack::new(quizzes))
+}
+
+/// Entries of a GIFT file and the lines they start at, without comments
+/// and category lines.
+fn gift_entries(source: &str) -> Vec<(usize, String)> {
+    let mut entries = Vec::new();
+    let mut entry: Option<(usize, String)> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            entries.extend(entry.take());
+        } else if !trimmed.starts_with("//") && !trimmed.starts_with("$CATEGORY:") {
+            let (_, text) = entry.get_or_insert_with(|| (i + 1, String::new()));
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+
+    entries.extend(entry);
+    entries
+}
+
+fn parse_gift_entry(entry: &str) -> Result<Option<Quiz>, String> {
+    let mut text = entry.trim();
+
+    // skip the title
+    if let Some(rest) = text.strip_prefix("::") {
+        let end = find_unescaped(rest, "::").ok_or("title is missing its closing ::")?;
+        text = rest[end + 2..].trim_start();
+    }
+
+    let Some(open) = find_unescaped(text, "{") else { return Ok(None) };
+    let close = open + find_unescaped(&text[open..], "}").ok_or("answers are missing their closing }")?;
+
+    let (mut before, body, after) = (&text[..open], text[open + 1..close].trim(), text[close + 1..].trim_end());
+
+    let mut html = false;
+    for format in ["[html]", "[moodle]", "[plain]", "[markdown]"] {
+        if let Some(rest) = before.strip_prefix(format) {
+            html = format == "[html]";
+            before = rest;
+        }
+    }
+
+    let question = if after.is_empty() {
+        before.trim().to_string()
+    } else {
+        format!("{} ____{}", before.trim(), after).trim_start().to_string()
+    };
+
+    let question = unescape(&question);
+    let question = if html { strip_html(&question) } else { question };
+
+    let body = match find_unescaped(body, "#") {
+        Some(0) => return Err("numeric questions are not supported".to_string()),
+        Some(feedback) if !body.starts_with(['=', '~']) => &body[..feedback],
+        _ => body,
+    };
+
+    let mut quiz = Quiz::new(question, String::new());
+
+    match body.trim().to_lowercase().as_str() {
+        "" => return Err("essay questions are not supported".to_string()),
+        "t" | "true" => {
+            quiz.kind = Kind::TrueFalse;
+            quiz.answer = Text::from("true");
+            return Ok(Some(quiz));
+        }
+        "f" | "false" => {
+            quiz.kind = Kind::TrueFalse;
+            quiz.answer = Text::from("false");
+            return Ok(Some(quiz));
+        }
+        _ => (),
+    }
+
+    let mut right: Vec<Text> = Vec::new();
+    let mut wrong: Vec<Text> = Vec::new();
+
+    for (marker, answer) in gift_answers(body)? {
+        if find_unescaped(answer, "->").is_some() {
+            return Err("matching questions are not supported".to_string());
+        }
+
+        // drop the feedback
+        let answer = find_unescaped(answer, "#").map_or(answer, |feedback| &answer[..feedback]);
+
+        let (weight, answer) = match answer.trim_start().strip_prefix('%').and_then(|rest| rest.split_once('%')) {
+            Some((weight, answer)) => (weight.trim().parse::<f64>().map_err(|_| format!("invalid answer weight {:?}", weight))?, answer),
+            None => (if marker == '=' { 100.0 } else { 0.0 }, answer),
+        };
+
+        let answer = Text::from(unescape(answer.trim()));
+        if weight > 0.0 { right.push(answer) } else { wrong.push(answer) }
+    }
+
+    match (right.len(), wrong.len()) {
+        (0, _) => return Err("no right answer".to_string()),
+        (_, 0) => {
+            quiz.kind = Kind::FreeText;
+            quiz.answer = right.remove(0);
+            quiz.alternatives = right;
+        }
+        (1, _) => {
+            quiz.answer = right.remove(0);
+            quiz.wrong_answers = wrong;
+        }
+        _ => {
+            quiz.kind = Kind::MultiSelect;
+            quiz.answers = right;
+            quiz.wrong_answers = wrong;
+        }
+    }
+
+    Ok(Some(quiz))
+}
+
+/// Splits the inside of `{...}` into answers, each with its `=` or `~`.
+fn gift_answers(body: &str) -> Result<Vec<(char, &str)>, String> {
+    let mut answers: Vec<(char, usize)> = Vec::new();
+    let mut escaped = false;
+
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' | '~' => answers.push((c, i)),
+            _ => (),
+        }
+    }
+
+    if answers.first().map(|&(_, start)| start) != Some(0) {
+        return Err(format!("expected answers starting with = or ~, got {:?}", body));
+    }
+
+    let ends = answers.iter().skip(1).map(|&(_, start)| start).chain(std::iter::once(body.len()));
+    Ok(answers.iter().zip(ends).map(|(&(marker, start), end)| (marker, &body[start + 1..end])).collect())
+}
+
+/// Byte index of the first `pattern` in `text` not preceded by `\`.
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[i..].starts_with(pattern) {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+/// Resolves GIFT escapes such as `\=`, `\{` and `\n`.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(escaped)) => {
+                unescaped.push(if escaped == 'n' { '\n' } else { escaped });
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gift(source: &str) -> Vec<Quiz> {
+        parse_gift(source).unwrap().quizzes
+    }
+
+    #[test]
+    fn gift_choice_keeps_wrong_answers_in_order() {
+        let quizzes = gift("// acquirers\n::Q1:: Who acquired iZettle? {=PayPal ~Square ~Adyen#nope}\n");
+
+        assert_eq!(quizzes.len(), 1);
+        assert_eq!(quizzes[0].kind, Kind::Choice);
+        assert_eq!(quizzes[0].question, "Who acquired iZettle?");
+        assert_eq!(quizzes[0].answer, "PayPal");
+        assert_eq!(quizzes[0].wrong_answers, vec!["Square", "Adyen"]);
+    }
+
+    #[test]
+    fn gift_true_false_free_text_and_blanks() {
+        let quizzes = gift("Zettle is Swedish. {T}\n\nWhat is 2 + 2? {=four =4}\n\nPayPal acquired iZettle in {=2018}.\n");
+
+        assert_eq!(quizzes[0].kind, Kind::TrueFalse);
+        assert_eq!(quizzes[0].answer, "true");
+        assert_eq!(quizzes[1].kind, Kind::FreeText);
+        assert_eq!(quizzes[1].answer, "four");
+        assert_eq!(quizzes[1].alternatives, vec!["4"]);
+        assert_eq!(quizzes[2].question, "PayPal acquired iZettle in ____.");
+    }
+
+    #[test]
+    fn gift_weights_make_multi_select() {
+        let quizzes = gift("Pick the acquirers {\n  ~%50%Elavon\n  ~%50%Nets\n  ~%-100%Spotify\n}\n");
+
+        assert_eq!(quizzes[0].kind, Kind::MultiSelect);
+        assert_eq!(quizzes[0].answers, vec!["Elavon", "Nets"]);
+        assert_eq!(quizzes[0].wrong_answers, vec!["Spotify"]);
+    }
+
+    #[test]
+    fn gift_resolves_escapes() {
+        let quizzes = gift("Is 1 \\= 1 in \\{braces\\}? {=yes \\~ sure ~no}");
+
+        assert_eq!(quizzes[0].question, "Is 1 = 1 in {braces}?");
+        assert_eq!(quizzes[0].answer, "yes ~ sure");
+    }
+
+    #[test]
+    fn gift_rejects_unsupported_questions() {
+        assert_eq!(parse_gift("\n\nPick a number {#3:1}").unwrap_err(), "line 3: numeric questions are not supported");
+        assert!(parse_gift("Match {=a -> 1 =b -> 2}").is_err());
+        assert!(gift("Just a description.").is_empty());
+    }
+
+    #[test]
+    fn csv_columns_by_header() {
+        let source = "\
+Question,Answer,Wrong1,Wrong2,Wrong3,Weight
+Who acquired iZettle?,PayPal,Square,,Adyen,2
+Which year?,2018,2017,2019,,
+";
+        let quizzes = parse_csv(source).unwrap().quizzes;
+
+        assert_eq!(quizzes.len(), 2);
+        assert_eq!(quizzes[0].kind, Kind::Choice);
+        assert_eq!(quizzes[0].answer, "PayPal");
+        assert_eq!(quizzes[0].wrong_answers, vec!["Square", "Adyen"]);
+        assert_eq!(quizzes[0].weight, 2.0);
+        assert_eq!(quizzes[1].wrong_answers, vec!["2017", "2019"]);
+        assert_eq!(quizzes[1].weight, 1.0);
+    }
+
+    #[test]
+    fn csv_kinds_and_multi_select_answers() {
+        let source = "\
+kind,question,answer,wrong1
+multi_select,Which are acquirers?,Elavon | Nets,Spotify
+true_false,Zettle is Swedish.,true,
+,Capital of Sweden?,Stockholm,Oslo
+";
+        let quizzes = parse_csv(source).unwrap().quizzes;
+
+        assert_eq!(quizzes[0].kind, Kind::MultiSelect);
+        assert_eq!(quizzes[0].answers, vec!["Elavon", "Nets"]);
+        assert!(quizzes[0].answer.is_empty());
+        assert_eq!(quizzes[1].kind, Kind::TrueFalse);
+        assert!(quizzes[1].wrong_answers.is_empty());
+        assert_eq!(quizzes[2].kind, Kind::Choice);
+    }
+
+    #[test]
+    fn csv_errors_name_the_line() {
+        assert_eq!(parse_csv("question,wrong1\nQ?,A\n").unwrap_err(), "missing answer column");
+        assert_eq!(parse_csv("question,answer,kind\nQ?,A,\nR?,B,essay\n").unwrap_err(), "line 3: unknown kind \"essay\"");
+        assert_eq!(parse_csv("question,answer,weight\nQ?,A,often\n").unwrap_err(), "line 2: invalid weight \"often\"");
+    }
+
+    #[test]
+    fn json_list_or_pack() {
+        let list = parse_json(r#"[{"question": "Who acquired iZettle?", "answer": "PayPal", "wrong_answers": ["Square"]}]"#).unwrap();
+        assert_eq!(list.name, "");
+        assert_eq!(list.quizzes[0].wrong_answers, vec!["Square"]);
+
+        let pack = parse_json(r#"{"name": "trivia", "weight": 3, "quizzes": [{"question": "Q?", "answer": "A"}]}"#).unwrap();
+        assert_eq!(pack.name, "trivia");
+        assert_eq!(pack.weight, 3.0);
+        assert_eq!(pack.quizzes[0].question, "Q?");
+
+        assert!(parse_json(r#"{"quizzes": [], "nmae": "trivia"}"#).is_err());
+    }
+
+    #[test]
+    fn anki_cards_become_free_text_quizzes() {
+        let source = "#separator:tab\n#html:true\n#tags column:3\n\
+            Who acquired iZettle?\tPayPal\tcompany\n\
+            Capital of Sweden?\tStockholm<br>(Sweden)\tgeo\n\
+            #include what?\t&lt;stdio.h&gt;\t\n";
+        let quizzes = parse_anki(source).unwrap().quizzes;
+
+        assert_eq!(quizzes.len(), 3);
+        assert_eq!(quizzes[0].kind, Kind::FreeText);
+        assert_eq!(quizzes[0].answer, "PayPal");
+        assert!(quizzes[0].wrong_answers.is_empty());
+        assert_eq!(quizzes[1].answer, "Stockholm (Sweden)");
+        assert_eq!(quizzes[2].question, "#include what?");
+        assert_eq!(quizzes[2].answer, "<stdio.h>");
+    }
+
+    #[test]
+    fn anki_reports_lines_after_the_headers() {
+        assert_eq!(parse_anki("#html:false\nfront\tback\nfront only\n").unwrap_err(), "line 3: expected a front and a back");
+    }
+}
@@ -0,0 +1,307 @@
+use std::cmp;
+
+use crate::buffer::{Attrs, Buffer};
+use crate::grid::{self, Block};
+use crate::locale::Strings;
+use crate::markup::Style;
+use crate::panel::{quiz_panel, CodeStyle};
+use crate::quiz::Mode;
+use crate::render::{Scale, View};
+use crate::text::{self, center};
+use crate::theme::{Glyph, Theme};
+
+pub const LAYOUT_QUIZ_WIDTH: u16 = 28;
+pub const LAYOUT_QUIZ_HEIGHT: u16 = grid::HEIGHT as u16 - 2;
+
+/// Width of the boxes drawn over the board.
+const BOX_WIDTH: usize = 12;
+
+/// Width of the status panel counters, between the brackets.
+const COUNTER_WIDTH: usize = 15;
+
+/// Where the quiz panel, the board and the status go on a screen of a
+/// given size, and how they are drawn into a `Buffer`. Shared by the
+/// renderers so they all show the same frame.
+pub struct Layout {
+    pub width: u16,
+    pub height: u16,
+    offset_x: u16,
+    offset_y: u16,
+    scale: Scale,
+    theme: Theme,
+    code_style: CodeStyle,
+    strings: &'static Strings,
+}
+
+impl Layout {
+    pub fn new(width: u16, height: u16, scale: Scale, theme: Theme, code_style: CodeStyle, strings: &'static Strings) -> Layout {
+        let mut layout = Layout {
+            width: 0,
+            height: 0,
+            offset_x: 0,
+            offset_y: 0,
+            scale,
+            theme,
+            code_style,
+            strings,
+        };
+
+        layout.resize(width, height);
+        layout
+    }
+
+    /// Centers the game on a screen of `width` by `height` as far as it
+    /// fits.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let (min_width, min_height) = self.size();
+        let grid_width = grid::WIDTH as u16 * self.scale.x as u16;
+
+        self.width = width;
+        self.height = height;
+
+        let offset_x = (width.saturating_sub(grid_width) / 2).saturating_sub(LAYOUT_QUIZ_WIDTH);
+        self.offset_x = offset_x.min(width.saturating_sub(min_width));
+
+        let offset_y = height.saturating_sub(grid::HEIGHT as u16 * self.scale.y as u16 + 8);
+        self.offset_y = offset_y.min(height.saturating_sub(min_height));
+    }
+
+    /// Columns and rows the game needs: the quiz panel, the board with
+    /// its walls and floor, and the help text or status beside it.
+    pub fn size(&self) -> (u16, u16) {
+        let grid_width = grid::WIDTH as u16 * self.scale.x as u16;
+        let help_width = self.help_text().iter().map(|line| text::width(line)).max().unwrap_or(0);
+        let side_width = cmp::max(2 + help_width, 4 + COUNTER_WIDTH + 2) as u16;
+
+        let grid_height = grid::HEIGHT as u16 * self.scale.y as u16 + 2;
+        let quiz_height = LAYOUT_QUIZ_HEIGHT + 3;
+
+        (LAYOUT_QUIZ_WIDTH + 2 + grid_width + side_width, cmp::max(grid_height, quiz_height))
+    }
+
+    pub fn fits(&self) -> bool {
+        let (width, height) = self.size();
+        self.width >= width && self.height >= height
+    }
+
+    /// Draws a frame of `view` into the blank `buffer`, or a message
+    /// asking for a bigger screen if the game does not fit.
+    pub fn paint(&self, view: &View, buffer: &mut Buffer) {
+        if !self.fits() {
+            self.draw_too_small(buffer);
+            return;
+        }
+
+        self.draw_layout(buffer);
+        self.draw_grid(view, buffer);
+
+        if view.grid.game_over {
+            self.draw_game_over(buffer);
+        }
+
+        self.draw_quiz(view, buffer);
+        self.draw_status(view, buffer);
+    }
+
+    fn draw_too_small(&self, buffer: &mut Buffer) {
+        let (width, height) = self.size();
+        let message = format!("{} {}x{}", self.strings.too_small, width, height);
+
+        let x = self.width.saturating_sub(text::width(&message) as u16) / 2 + 1;
+        let y = self.height / 2 + 1;
+        buffer.put(x, y, &message, Attrs::default());
+    }
+
+    /// Key help shown right of the board, from its third row.
+    fn help_text(&self) -> [String; 5] {
+        [
+            format!("  ↑ / k: {}", self.strings.rotate),
+            format!("  ← / h: {}", self.strings.move_left),
+            format!("  → / l: {}", self.strings.move_right),
+            format!("  ↓ / j: {}", self.strings.move_down),
+            format!("      r: {}", self.strings.reset),
+        ]
+    }
+
+    // quiz | tetris grid | status
+    fn draw_layout(&self, buffer: &mut Buffer) {
+        let help = self.help_text();
+
+        for y in 1..(grid::HEIGHT * self.scale.y) + 2 {
+            let help_text = (y as usize).checked_sub(3).and_then(|i| help.get(i)).map_or("", |line| line.as_str());
+
+            let grid_width = grid::WIDTH * self.scale.x;
+            let x = self.offset_x + LAYOUT_QUIZ_WIDTH;
+            let y = self.offset_y + y as u16;
+            put_glyph(buffer, x, y, &self.theme.walls[0]);
+
+            put_glyph(buffer, x + grid_width as u16 + 2, y, &self.theme.walls[1]);
+            buffer.put(x + grid_width as u16 + 4, y, help_text, Attrs::default());
+        }
+
+        let x = self.offset_x + LAYOUT_QUIZ_WIDTH + 2;
+        let y = self.offset_y + grid::HEIGHT as u16 * self.scale.y as u16 + 1;
+        let grid_width = grid::WIDTH as usize * self.scale.x as usize;
+
+        for (row, glyph) in self.theme.floor.iter().enumerate() {
+            buffer.put(x, y + row as u16, &fit(&glyph.text, grid_width), Attrs::paint(glyph.paint));
+        }
+    }
+
+    fn draw_grid(&self, view: &View, buffer: &mut Buffer) {
+        let offset_x = self.offset_x + LAYOUT_QUIZ_WIDTH + 2;
+        let ghost = view.grid.ghost().filter(|_| self.theme.ghost.is_some());
+
+        for i in 0..view.grid.cells.len() {
+            let y = i as u8 / grid::WIDTH;
+            let x = i as u8 % grid::WIDTH;
+
+            let x = offset_x + x as u16 * self.scale.x as u16;
+            let y = self.offset_y + y as u16 * self.scale.y as u16 + 1;
+
+            let (text, attrs) = match view.grid.cells[i] {
+                Block::Empty if ghost.is_some_and(|ghost| ghost.contains(&(i as i16))) => {
+                    let glyph = self.theme.ghost.as_ref().unwrap();
+                    (&glyph.text, Attrs::paint(glyph.paint))
+                }
+                Block::Empty => (&self.theme.empty.text, Attrs::paint(self.theme.empty.paint)),
+                Block::Garbage => (&self.theme.garbage.text, Attrs::paint(self.theme.garbage.paint)),
+                Block::Tetromino(kind) => {
+                    let mut attrs = Attrs::paint(self.theme.cell.paint);
+                    if !self.theme.pieces.is_empty() {
+                        attrs.fg = Some(self.theme.pieces[kind % self.theme.pieces.len()]);
+                    }
+
+                    let text = match self.theme.patterns.len() {
+                        0 => &self.theme.cell.text,
+                        len => &self.theme.patterns[kind % len],
+                    };
+
+                    (text, attrs)
+                }
+            };
+
+            let text = fit(text, self.scale.x as usize);
+            for row in 0..self.scale.y as u16 {
+                buffer.put(x, y + row, &text, attrs);
+            }
+        }
+
+        if view.quiz.is_some() {
+            match view.mode {
+                Mode::Lock => self.draw_quiz_lock(self.strings.locked, buffer),
+                Mode::Freeze => self.draw_quiz_lock(self.strings.frozen, buffer),
+                Mode::Juggle => (),
+            }
+        }
+    }
+
+    /// Width of the boxes drawn over the board, narrower than usual if
+    /// the board is.
+    fn box_width(&self) -> usize {
+        BOX_WIDTH.min(grid::WIDTH as usize * self.scale.x as usize)
+    }
+
+    /// Where boxes drawn over the board go: centered across it, from its
+    /// fourth row of cells.
+    fn box_position(&self) -> (u16, u16) {
+        let grid_width = grid::WIDTH as u16 * self.scale.x as u16;
+        let x = (self.offset_x + LAYOUT_QUIZ_WIDTH + 2 + grid_width / 2).saturating_sub(self.box_width() as u16 / 2).max(1);
+        let y = self.offset_y + 3 * self.scale.y as u16 + 1;
+
+        (x, y)
+    }
+
+    fn draw_quiz_lock(&self, text: &str, buffer: &mut Buffer) {
+        let (x, y) = self.box_position();
+        let width = self.box_width();
+        let (top, bottom) = self.theme.box_edges(width);
+
+        buffer.put(x, y, &top, Attrs::default());
+        buffer.put(x, y + 1, &center(&box_label(text, width), width, false), Attrs::default());
+        buffer.put(x, y + 2, &bottom, Attrs::default());
+    }
+
+    fn draw_game_over(&self, buffer: &mut Buffer) {
+        let (x, y) = self.box_position();
+
+        let width = self.box_width();
+        let [game, over] = self.strings.game_over;
+        let (top, bottom) = self.theme.box_edges(width);
+
+        buffer.put(x, y, &top, Attrs::default());
+        buffer.put(x, y + 1, &center(&box_label(game, width), width, false), Attrs::default());
+        buffer.put(x, y + 2, &" ".repeat(width), Attrs::default());
+        buffer.put(x, y + 3, &center(&box_label(over, width), width, true), Attrs::default());
+        buffer.put(x, y + 4, &bottom, Attrs::default());
+    }
+
+    fn draw_status(&self, view: &View, buffer: &mut Buffer) {
+        let grid_width = grid::WIDTH * self.scale.x;
+        let offset_x = self.offset_x + LAYOUT_QUIZ_WIDTH + grid_width as u16 + 6;
+        let offset_y = self.offset_y + grid::HEIGHT as u16 * self.scale.y as u16;
+
+        buffer.put(offset_x, offset_y - 4, &counter(self.strings.score, view.grid.score as u64), Attrs::default());
+        buffer.put(offset_x, offset_y - 2, &counter(self.strings.level, view.grid.level as u64), Attrs::default());
+        buffer.put(offset_x, offset_y, &counter(self.strings.lines, view.grid.cleared as u64), Attrs::default());
+    }
+
+    fn draw_quiz(&self, view: &View, buffer: &mut Buffer) {
+        if let Some(quiz) = view.quiz {
+            let x = self.offset_x + 2;
+            let y = self.offset_y + 2;
+
+            for (row, line) in quiz_panel(quiz, LAYOUT_QUIZ_WIDTH - 4, LAYOUT_QUIZ_HEIGHT).iter().enumerate() {
+                let mut x = x;
+                for span in line.iter() {
+                    let mut attrs = attrs(span.style, self.code_style);
+                    attrs.fg = self.theme.panel.fg;
+                    attrs.bg = self.theme.panel.bg;
+
+                    buffer.put(x, y + row as u16, &span.text, attrs);
+                    x += text::width(&span.text) as u16;
+                }
+            }
+        }
+    }
+}
+
+/// A status panel counter such as `[SCORE:      120]`.
+fn counter(label: &str, value: u64) -> String {
+    format!("[{} {: >2$}]", label, value, COUNTER_WIDTH.saturating_sub(text::width(label) + 1))
+}
+
+fn put_glyph(buffer: &mut Buffer, x: u16, y: u16, glyph: &Glyph) {
+    buffer.put(x, y, &glyph.text, Attrs::paint(glyph.paint));
+}
+
+/// `glyph` repeated or cut to `width` columns, dropping its padding first
+/// if it is too wide, so that ` .` still shows a dot in one column.
+fn fit(glyph: &str, width: usize) -> String {
+    let trimmed = glyph.trim();
+    if text::width(glyph) > width && !trimmed.is_empty() {
+        return text::fill(trimmed, width);
+    }
+
+    text::fill(glyph, width)
+}
+
+/// `label` cut to `width` columns, dropping the spaces of a spaced out
+/// label such as `G A M E` first if it is too wide.
+fn box_label(label: &str, width: usize) -> String {
+    if text::width(label) > width {
+        return text::truncate(&label.replace(' ', ""), width);
+    }
+
+    label.to_string()
+}
+
+/// How a span of quiz text is drawn.
+fn attrs(span_style: Style, code_style: CodeStyle) -> Attrs {
+    Attrs {
+        bold: span_style.bold || (span_style.code && code_style == CodeStyle::Bold),
+        italic: span_style.italic,
+        invert: span_style.code && code_style == CodeStyle::Invert,
+        ..Attrs::default()
+    }
+}
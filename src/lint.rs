@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rand::thread_rng;
+
+use crate::import::{self, Format};
+use crate::layout::{LAYOUT_QUIZ_HEIGHT, LAYOUT_QUIZ_WIDTH};
+use crate::locale::{self, Strings};
+use crate::panel::{code_lines, CurrentQuiz};
+use crate::quiz::{self, Kind, Pack, Quiz};
+use crate::text;
+
+pub struct Problem {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(line: Option<usize>, message: String) -> Problem {
+        Problem { line, message }
+    }
+}
+
+/// Lints every file, printing problems as `file:line: message`.
+/// Returns the number of problems found.
+pub fn run(files: &[PathBuf]) -> usize {
+    let mut questions = HashMap::new();
+    let mut count = 0;
+
+    for file in files.iter() {
+        let name = file.display().to_string();
+
+        let problems = match Format::from_path(file) {
+            Some(Format::Yaml) => match fs::read_to_string(file) {
+                Ok(source) => lint(&source, &name, &mut questions),
+                Err(err) => vec![Problem::new(None, err.to_string())],
+            },
+
+            // other formats are linted without line numbers
+            _ => match import::load(file) {
+                Ok(pack) => lint_pack(&pack, &[], &name, &mut questions),
+                Err(err) => vec![Problem::new(None, err)],
+            },
+        };
+
+        for problem in problems.iter() {
+            match problem.line {
+                Some(line) => println!("{}:{}: {}", name, line, problem.message),
+                None => println!("{}: {}", name, problem.message),
+            }
+        }
+
+        count += problems.len();
+    }
+
+    if count > 0 {
+        println!("{} problem(s) found", count);
+    }
+
+    count
+}
+
+/// Quizzes seen so far, to catch duplicates across packs. Maps each
+/// question and its options to where it was first seen and its answers.
+pub type Seen = HashMap<String, (String, String)>;
+
+/// Lints one YAML quiz pack.
+pub fn lint(source: &str, name: &str, questions: &mut Seen) -> Vec<Problem> {
+    match Pack::parse(source) {
+        Ok(pack) => lint_pack(&pack, &entry_lines(source), name, questions),
+        Err(err) => {
+            let line = err.location().map(|location| location.line());
+            vec![Problem::new(line, err.to_string())]
+        }
+    }
+}
+
+/// Lints a parsed pack, `lines` holds the line each quiz starts at if
+/// known. A question asked twice with the same options is reported, more
+/// loudly if the answers disagree. The same question may be asked again
+/// with other options.
+pub fn lint_pack(pack: &Pack, lines: &[usize], name: &str, questions: &mut Seen) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    if let Err(err) = quiz::validate_weight(pack.weight) {
+        problems.push(Problem::new(None, err));
+    }
+
+    for (i, quiz) in pack.quizzes.iter().enumerate() {
+        let line = lines.get(i).copied();
+
+        // without a line, point at the quiz by its position instead
+        let prefix = match line {
+            Some(_) => String::new(),
+            None => format!("quiz {}: ", i + 1),
+        };
+
+        for message in lint_quiz(quiz) {
+            problems.push(Problem::new(line, format!("{}{}", prefix, message)));
+        }
+
+        let location = match line {
+            Some(line) => format!("{}:{}", name, line),
+            None => format!("{} quiz {}", name, i + 1),
+        };
+
+        let key = question_key(quiz);
+        let answers = answers_key(quiz);
+
+        match questions.get(&key) {
+            Some((seen, seen_answers)) if *seen_answers != answers => {
+                problems.push(Problem::new(line, format!("{}same question with a different answer, first seen at {}", prefix, seen)));
+            }
+            Some((seen, _)) => problems.push(Problem::new(line, format!("{}duplicate question, first seen at {}", prefix, seen))),
+            None => {
+                questions.insert(key, (location, answers));
+            }
+        }
+    }
+
+    problems
+}
+
+/// The question of `quiz` with the options the player picks from,
+/// normalized and in order. Free-text and true/false quizzes have no
+/// options of their own, so their question alone is the key.
+fn question_key(quiz: &Quiz) -> String {
+    let mut options: Vec<String> = match quiz.kind {
+        Kind::Choice => std::iter::once(&quiz.answer).chain(quiz.wrong_answers.iter()).map(|option| quiz::normalize(option)).collect(),
+        Kind::MultiSelect => quiz.answers.iter().chain(quiz.wrong_answers.iter()).map(|option| quiz::normalize(option)).collect(),
+        Kind::TrueFalse | Kind::FreeText => Vec::new(),
+    };
+    options.sort();
+
+    format!("{}\n{}", quiz::normalize(&quiz.question), options.join("\n"))
+}
+
+/// The correct answers of `quiz`, normalized and in order, to compare
+/// them with another quiz.
+fn answers_key(quiz: &Quiz) -> String {
+    let mut answers: Vec<String> = quiz.answers.iter().map(|answer| quiz::normalize(answer)).collect();
+    answers.sort();
+
+    format!("{}\n{}", quiz::normalize(&quiz.answer), answers.join("\n"))
+}
+
+/// Lints the quiz as a whole, then each of its languages, prefixing
+/// problems found in a language with its code.
+pub fn lint_quiz(quiz: &Quiz) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    if let Err(err) = quiz.validate() {
+        messages.push(err);
+    }
+
+    let languages = quiz.languages();
+    if languages.is_empty() {
+        messages.extend(lint_language(quiz, &locale::EN));
+    }
+
+    for language in languages {
+        let untranslated = quiz.texts().filter(|text| !text.languages().is_empty() && !text.languages().contains(&language));
+        for text in untranslated {
+            messages.push(format!("{}: {:?} has no translation, the English one is shown", language, &**text));
+        }
+
+        for message in lint_language(&quiz.localize(language), locale::strings(language)) {
+            messages.push(format!("{}: {}", language, message));
+        }
+    }
+
+    messages
+}
+
+fn lint_language(quiz: &Quiz, strings: &'static Strings) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    let mut texts = std::iter::once(&quiz.question)
+        .chain(quiz.answers.iter())
+        .chain(quiz.wrong_answers.iter())
+        .chain(quiz.alternatives.iter());
+
+    if texts.any(|text| text.trim().is_empty()) {
+        messages.push("contains an empty string".to_string());
+    }
+
+    let mut correct: Vec<String> = quiz.answers.iter().map(|answer| quiz::normalize(answer)).collect();
+    if !quiz.answer.is_empty() {
+        correct.push(quiz::normalize(&quiz.answer));
+    }
+
+    let mut seen: Vec<String> = Vec::new();
+    for answer in quiz.wrong_answers.iter().map(|answer| quiz::normalize(answer)) {
+        if correct.contains(&answer) {
+            messages.push(format!("wrong answer {:?} is also a correct answer", answer));
+        } else if seen.contains(&answer) {
+            messages.push(format!("wrong answer {:?} is listed twice", answer));
+        }
+        seen.push(answer);
+    }
+
+    let width = (LAYOUT_QUIZ_WIDTH - 4) as usize;
+    for (i, line) in code_lines(&quiz.code).iter().enumerate() {
+        if text::width(line) > width {
+            messages.push(format!("code line {} is wider than the quiz panel ({} columns)", i + 1, width));
+        }
+    }
+
+    let height = quiz_height(quiz, strings);
+    if height > LAYOUT_QUIZ_HEIGHT {
+        messages.push(format!("needs {} lines, the quiz panel shows {} without scrolling", height, LAYOUT_QUIZ_HEIGHT));
+    }
+
+    messages
+}
+
+/// Lines `draw_quiz` needs to show `quiz` without scrolling.
+fn quiz_height(quiz: &Quiz, strings: &'static Strings) -> u16 {
+    CurrentQuiz::from_quiz(quiz, strings, &mut thread_rng()).lines(LAYOUT_QUIZ_WIDTH - 4).len() as u16
+}
+
+/// Lines where the quiz entries start: the least indented sequence items.
+/// Only block style sequences are recognised.
+fn entry_lines(source: &str) -> Vec<usize> {
+    let items: Vec<(usize, usize)> = source.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let item = line.trim_start();
+            (item == "-" || item.starts_with("- ")).then(|| (i + 1, line.len() - item.len()))
+        })
+        .collect();
+
+    let indent = items.iter().map(|&(_, indent)| indent).min().unwrap_or(0);
+
+    items.iter()
+        .filter(|&&(_, item_indent)| item_indent == indent)
+        .map(|&(line, _)| line)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str, questions: &mut Seen) -> Vec<String> {
+        lint(source, "pack.yaml", questions).into_iter().map(|problem| problem.message).collect()
+    }
+
+    #[test]
+    fn built_in_quizzes_lint_clean() {
+        assert_eq!(messages(crate::QUIZZES, &mut Seen::new()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn same_question_with_other_options_is_not_a_duplicate() {
+        let source = "\
+- question: Which one is NOT one of our acquirers?
+  answer: SoftSpace
+  wrong_answers: [Amex, Evry]
+- question: Which one is NOT one of our acquirers?
+  answer: Datecs
+  wrong_answers: [Elavon, Evry]
+- question: Which one is NOT one of our acquirers?
+  answer: Amex
+  wrong_answers: [SoftSpace, Evry]
+";
+        assert_eq!(messages(source, &mut Seen::new()), vec!["same question with a different answer, first seen at pack.yaml:1"]);
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_with_their_line() {
+        let problems = lint("- question: Q?\n  answer: A\n  wrong_answers: [B]\n  weigth: 2\n", "pack.yaml", &mut Seen::new());
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(4));
+        assert!(problems[0].message.contains("unknown field `weigth`"));
+    }
+
+    #[test]
+    fn duplicates_are_caught_across_packs() {
+        let mut questions = Seen::new();
+        let quiz = "- question: Who acquired iZettle?\n  answer: PayPal\n  wrong_answers: [Square]\n";
+
+        assert!(messages(quiz, &mut questions).is_empty());
+        assert_eq!(lint(quiz, "other.yaml", &mut questions)[0].message, "duplicate question, first seen at pack.yaml:1");
+    }
+
+    #[test]
+    fn problems_point_at_the_line_their_quiz_starts() {
+        let source = "\
+# acquirers
+- question: Who acquired iZettle?
+  answer: PayPal
+  wrong_answers:
+    - Square
+    - PayPal
+
+- question: Is Zettle Swedish?
+  kind: true_false
+  answer: yes
+";
+        let lines: Vec<Option<usize>> = lint(source, "pack.yaml", &mut Seen::new()).iter().map(|problem| problem.line).collect();
+
+        assert_eq!(entry_lines(source), vec![2, 8]);
+        assert_eq!(lines, vec![Some(2), Some(8)]);
+    }
+
+    #[test]
+    fn entry_lines_skip_nested_lists() {
+        let source = "name: pack\nquizzes:\n  - question: A?\n    wrong_answers:\n      - B\n  - question: C?\n";
+
+        assert_eq!(entry_lines(source), vec![3, 6]);
+    }
+
+    #[test]
+    fn every_language_is_linted() {
+        let quiz: Quiz = serde_yaml::from_str("\
+question: {en: Who acquired iZettle?, sv: Vem köpte iZettle?}
+answer: {en: PayPal, sv: PayPal}
+wrong_answers:
+  - {en: Square, sv: PayPal}
+  - {en: Adyen}
+").unwrap();
+
+        assert_eq!(lint_quiz(&quiz), vec![
+            "sv: \"Adyen\" has no translation, the English one is shown",
+            "sv: wrong answer \"paypal\" is also a correct answer",
+        ]);
+    }
+
+    #[test]
+    fn run_counts_problems_in_every_file() {
+        let dir = std::env::temp_dir();
+        let good = dir.join(format!("tequiz-lint-{}-good.yaml", std::process::id()));
+        let bad = dir.join(format!("tequiz-lint-{}-bad.yaml", std::process::id()));
+        fs::write(&good, "- question: Who acquired iZettle?\n  answer: PayPal\n  wrong_answers: [Square]\n").unwrap();
+        fs::write(&bad, "- question: Who acquired iZettle?\n  answer: PayPal\n  wrong_answers: [Square]\n- question: Q?\n  answer: \"\"\n").unwrap();
+
+        let count = run(&[good.clone(), bad.clone()]);
+        fs::remove_file(good).unwrap();
+        fs::remove_file(bad).unwrap();
+
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,201 @@
+use std::env;
+
+/// Language quizzes and the UI fall back to.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Text shown around the board and in the quiz panel.
+pub struct Strings {
+    pub rotate: &'static str,
+    pub move_left: &'static str,
+    pub move_right: &'static str,
+    pub move_down: &'static str,
+    pub reset: &'static str,
+
+    /// Spaced out to fit the board, one word per line.
+    pub game_over: [&'static str; 2],
+    pub locked: &'static str,
+    pub frozen: &'static str,
+
+    pub score: &'static str,
+    pub level: &'static str,
+    pub lines: &'static str,
+
+    pub confirm: &'static str,
+    pub true_: &'static str,
+    pub false_: &'static str,
+
+    pub question: &'static str,
+    pub result: &'static str,
+    pub correct: &'static str,
+    pub wrong: &'static str,
+    pub partly_right: &'static str,
+    pub next: &'static str,
+    pub quit: &'static str,
+    pub missed: &'static str,
+
+    pub too_small: &'static str,
+
+    pub select: &'static str,
+    pub move_quiz: &'static str,
+    pub add: &'static str,
+    pub edit: &'static str,
+    pub delete: &'static str,
+    pub save: &'static str,
+    pub change: &'static str,
+    pub back: &'static str,
+    pub done: &'static str,
+    pub cancel: &'static str,
+    pub new_line: &'static str,
+    pub separator_in_item: &'static str,
+    pub yes: &'static str,
+    pub no: &'static str,
+    pub any_other_key: &'static str,
+    pub quit_anyway: &'static str,
+
+    pub modified: &'static str,
+    pub no_quizzes: &'static str,
+    pub add_one: &'static str,
+    pub new_question: &'static str,
+    pub unsaved: &'static str,
+    pub delete_quiz: &'static str,
+    pub saved_to: &'static str,
+    pub cannot_save: &'static str,
+}
+
+pub const EN: Strings = Strings {
+    rotate: "rotate",
+    move_left: "move left",
+    move_right: "move right",
+    move_down: "move down",
+    reset: "reset",
+
+    game_over: ["G A M E", "O V E R"],
+    locked: "locked",
+    frozen: "frozen",
+
+    score: "SCORE:",
+    level: "LEVEL:",
+    lines: "LINES:",
+
+    confirm: "confirm",
+    true_: "True",
+    false_: "False",
+
+    question: "Question",
+    result: "Score",
+    correct: "Correct!",
+    wrong: "Wrong, the answer is",
+    partly_right: "Partly right, the answer is",
+    next: "next question",
+    quit: "quit",
+    missed: "Missed questions:",
+
+    too_small: "terminal too small, need",
+
+    select: "select",
+    move_quiz: "move",
+    add: "add",
+    edit: "edit",
+    delete: "delete",
+    save: "save",
+    change: "change",
+    back: "back",
+    done: "done",
+    cancel: "cancel",
+    new_line: "new line",
+    separator_in_item: "| in a list item",
+    yes: "yes",
+    no: "no",
+    any_other_key: "any other key",
+    quit_anyway: "quit anyway",
+
+    modified: "modified",
+    no_quizzes: "no quizzes yet",
+    add_one: "add one",
+    new_question: "New question",
+    unsaved: "unsaved changes",
+    delete_quiz: "delete this quiz?",
+    saved_to: "saved to",
+    cannot_save: "cannot save",
+};
+
+pub const SV: Strings = Strings {
+    rotate: "rotera",
+    move_left: "flytta vänster",
+    move_right: "flytta höger",
+    move_down: "flytta ner",
+    reset: "börja om",
+
+    game_over: ["S P E L E T", "S L U T"],
+    locked: "låst",
+    frozen: "fryst",
+
+    score: "POÄNG:",
+    level: "NIVÅ:",
+    lines: "RADER:",
+
+    confirm: "bekräfta",
+    true_: "Sant",
+    false_: "Falskt",
+
+    question: "Fråga",
+    result: "Poäng",
+    correct: "Rätt!",
+    wrong: "Fel, svaret är",
+    partly_right: "Delvis rätt, svaret är",
+    next: "nästa fråga",
+    quit: "avsluta",
+    missed: "Missade frågor:",
+
+    too_small: "terminalen är för liten, behöver",
+
+    select: "välj",
+    move_quiz: "flytta",
+    add: "lägg till",
+    edit: "ändra",
+    delete: "ta bort",
+    save: "spara",
+    change: "ändra",
+    back: "tillbaka",
+    done: "klar",
+    cancel: "avbryt",
+    new_line: "ny rad",
+    separator_in_item: "| i ett listelement",
+    yes: "ja",
+    no: "nej",
+    any_other_key: "annan tangent",
+    quit_anyway: "avsluta ändå",
+
+    modified: "ändrad",
+    no_quizzes: "inga frågor än",
+    add_one: "lägg till en",
+    new_question: "Ny fråga",
+    unsaved: "osparade ändringar",
+    delete_quiz: "ta bort frågan?",
+    saved_to: "sparade till",
+    cannot_save: "kan inte spara",
+};
+
+/// UI strings for `language`, English if there is no translation.
+pub fn strings(language: &str) -> &'static Strings {
+    match language {
+        "sv" => &SV,
+        _ => &EN,
+    }
+}
+
+/// The language from `--lang` if given, otherwise from `LANG`, such as
+/// `sv` for `sv_SE.UTF-8`.
+pub fn language(arg: Option<&str>) -> String {
+    let lang = match arg {
+        Some(lang) => lang.to_string(),
+        None => env::var("LANG").unwrap_or_default(),
+    };
+
+    let code = lang.split(['_', '-', '.', '@']).next().unwrap_or("").to_lowercase();
+
+    match code.as_str() {
+        "" | "c" | "posix" => DEFAULT_LANGUAGE.to_string(),
+        _ => code,
+    }
+}
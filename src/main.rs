@@ -1,276 +1,434 @@
+mod buffer;
+mod deck;
+mod edit;
 mod grid;
+#[cfg(test)]
+mod headless;
+mod import;
+mod layout;
+mod lint;
+mod locale;
+mod markup;
+mod panel;
 mod quiz;
-
-use clap::Parser;
-use rand::{Rng, thread_rng};
-use rand::seq::SliceRandom;
-use termion::{async_stdin, clear, cursor, style};
-use termion::raw::{IntoRawMode, RawTerminal};
-
-use std::io::{self, Read, Write, Result};
-use std::{time, thread, cmp};
-
-use quiz::Quiz;
+mod render;
+mod study;
+mod terminal;
+mod text;
+mod theme;
+mod trigger;
+
+use clap::{Parser, Subcommand};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use termion::async_stdin;
+
+use std::io::{self, Read, Result};
+use std::{time, thread, cmp, process};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use deck::{Deck, Policy};
+use edit::Editor;
+use locale::Strings;
+use render::{Renderer, Scale, View};
+use study::Study;
+use layout::{LAYOUT_QUIZ_HEIGHT, LAYOUT_QUIZ_WIDTH};
+use panel::{CodeStyle, CurrentQuiz};
+use terminal::{terminal_size, Terminal, ARROW_DOWN, ARROW_LEFT, ARROW_RIGHT, ARROW_UP, BACKSPACE, PAGE_DOWN, PAGE_UP};
+use theme::Palette;
+use quiz::{Kind, Mode, Pack, Quiz, Scoring};
+use trigger::{Strategy, Trigger};
 
 const QUIZZES: &str = include_str!("quizzes.yaml");
+const BUILTIN_PACK: &str = "builtin";
 
-const LAYOUT_QUIZ_WIDTH: u16 = 28;
+/// Time between two frames of the game.
+const TICK: time::Duration = time::Duration::from_millis(50);
 
-const ARROW_UP: (u8, u8, u8) = (27, 91, 65);
-const ARROW_DOWN: (u8, u8, u8) = (27, 91, 66);
-const ARROW_LEFT: (u8, u8, u8) = (27, 91, 68);
-const ARROW_RIGHT: (u8, u8, u8) = (27, 91, 67);
+/// Slack when adding up partly wrong answers into garbage rows.
+const PENALTY_EPSILON: f64 = 1e-9;
 
 #[derive(Parser, Debug)]
 struct Args {
     /// Set the chance that quizzes pop up (0-100)
     #[arg(long, value_name = "QUIZ RATIO", default_value_t = 20)]
     quiz_ratio: u8,
+
+    /// Set what makes quizzes pop up
+    #[arg(long, value_enum, default_value_t = Strategy::Piece)]
+    trigger: Strategy,
+
+    /// Set how many pieces the every-n trigger waits for
+    #[arg(long, value_name = "PIECES", default_value_t = 5)]
+    every: u32,
+
+    /// Set how many seconds of play the interval trigger waits for
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    interval: u64,
+
+    /// Set how an open quiz affects the game
+    #[arg(long, value_enum, default_value_t = Mode::Lock)]
+    quiz_mode: Mode,
+
+    /// Set how code blocks and `inline code` in quizzes are drawn
+    #[arg(long, global = true, value_enum, default_value_t = CodeStyle::Invert)]
+    code_style: CodeStyle,
+
+    /// Set how multi-select answers are judged
+    #[arg(long, global = true, value_enum, default_value_t = Scoring::AllOrNothing)]
+    scoring: Scoring,
+
+    /// Set how quizzes are picked to avoid repeating questions
+    #[arg(long, value_enum, default_value_t = Policy::Deck)]
+    repeat_policy: Policy,
+
+    /// Set how many recent quizzes the cooldown policy skips
+    #[arg(long, value_name = "QUIZZES", default_value_t = 5)]
+    cooldown: usize,
+
+    /// Load quizzes from these packs instead of the built-in one
+    #[arg(long, global = true, value_name = "FILE")]
+    quizzes: Vec<PathBuf>,
+
+    /// Override how often quizzes from a pack are picked (repeatable)
+    #[arg(long, global = true, value_name = "PACK=WEIGHT", value_parser = parse_pack_weight)]
+    pack_weight: Vec<(String, f64)>,
+
+    /// Set the seed of the random pieces and quizzes, to replay a game
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Set how the board looks: classic, blocks, ascii, dots or a YAML theme file
+    #[arg(long, value_name = "THEME", default_value = theme::DEFAULT_THEME)]
+    theme: String,
+
+    /// Set the columns and rows each cell of the board takes, such as 1x1 or 4x2
+    #[arg(long, value_name = "COLUMNSxROWS", default_value = render::DEFAULT_SCALE)]
+    scale: Scale,
+
+    /// Color the pieces so they stay apart with a kind of color blindness
+    #[arg(long, value_enum)]
+    palette: Option<Palette>,
+
+    /// Draw the board and quizzes bright white on black
+    #[arg(long)]
+    high_contrast: bool,
+
+    /// Give each kind of piece its own pattern rather than just a color
+    #[arg(long)]
+    patterns: bool,
+
+    /// Set the language of quizzes and the UI, taken from LANG by default
+    #[arg(long, global = true, value_name = "LANGUAGE")]
+    lang: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check quiz packs for mistakes
+    Lint {
+        /// Quiz pack files (YAML)
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Convert a quiz pack, such as an Anki export or a GIFT file, to YAML
+    Convert {
+        /// Quiz pack file
+        file: PathBuf,
+
+        /// Read the file as this format instead of guessing from its extension
+        #[arg(long, value_enum)]
+        from: Option<import::Format>,
+
+        /// Write the YAML here instead of to standard output
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Edit a YAML quiz pack, previewing quizzes as the game shows them
+    Edit {
+        /// Quiz pack file (YAML), created when saved if it does not exist
+        file: PathBuf,
+    },
+
+    /// Drill the quizzes without playing, then list the missed ones
+    Study {
+        /// Ask only this many quizzes instead of all of them
+        #[arg(long, value_name = "QUIZZES")]
+        count: Option<usize>,
+    },
 }
 
 fn main() {
     let args = Args::parse();
+
+    match &args.command {
+        Some(Command::Lint { files }) => {
+            let problems = lint::run(files);
+            process::exit(if problems > 0 { 1 } else { 0 });
+        }
+
+        Some(Command::Convert { file, from, output }) => {
+            if let Err(err) = convert(file, *from, output.as_deref()) {
+                eprintln!("{}: {}", file.display(), err);
+                process::exit(1);
+            }
+            process::exit(0);
+        }
+
+        Some(Command::Edit { file }) => {
+            let (pack, as_list) = match edit::open(file) {
+                Ok(opened) => opened,
+                Err(err) => {
+                    eprintln!("{}: {}", file.display(), err);
+                    process::exit(1);
+                }
+            };
+
+            let (term_width, term_height) = terminal_size();
+            let language = locale::language(args.lang.as_deref());
+            let stdout = io::stdout();
+
+            let mut editor = Editor::new(async_stdin(), stdout.lock(), term_width, term_height, file.clone(), pack, as_list, language, args.code_style);
+            if let Err(err) = editor.run() {
+                eprint!("{}", err);
+            }
+            return;
+        }
+
+        Some(Command::Study { .. }) | None => (),
+    }
     let quiz_ratio = cmp::min(args.quiz_ratio, 100);
 
+    let language = locale::language(args.lang.as_deref());
+
+    let quizzes = match load_quizzes(&args.quizzes, &args.pack_weight, &language) {
+        Ok(quizzes) => quizzes,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    let mut theme = match theme::load(&args.theme) {
+        Ok(theme) => theme,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    if args.high_contrast {
+        theme.set_high_contrast();
+    }
+
+    if let Some(palette) = args.palette {
+        theme.set_palette(palette);
+    }
+
+    if args.patterns {
+        theme.set_patterns();
+    }
+
     let stdout = io::stdout();
     let stdin = async_stdin();
 
     // get console info
-    let (term_width, term_height) = termion::terminal_size().ok().unwrap();
+    let (term_width, term_height) = terminal_size();
+
+    if let Some(Command::Study { count }) = args.command {
+        let strings = locale::strings(&language);
+        // leave the alternate screen before printing the report
+        let report = Study::new(stdin, stdout.lock(), term_width, term_height, quizzes, count, args.code_style, args.scoring, strings).run();
+
+        match report {
+            Ok(report) => report.print(strings),
+            Err(err) => eprint!("{}", err),
+        }
+        return;
+    }
 
-    let scale = Scale::new(2, 1);
+    let strings = locale::strings(&language);
+    let renderer = Terminal::new(stdout.lock(), term_width, term_height, args.scale, theme, args.code_style, strings);
 
     let mut game = Game::new(
         stdin,
-        stdout.lock(),
-        term_width,
-        term_height,
-        scale,
-        quiz_ratio,
+        renderer,
+        quizzes,
+        Trigger::new(args.trigger, quiz_ratio, args.every, time::Duration::from_secs(args.interval)),
+        args.quiz_mode,
+        args.scoring,
+        Deck::new(args.repeat_policy, args.cooldown),
+        strings,
+        args.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
     );
 
-    if let Err(err) = game.run() {
-        write!(io::stderr(), "{}", err).unwrap();
-    }
-}
+    let result = game.run();
+    let score = game.final_score();
 
-struct Scale {
-    pub x: u8,
-    pub y: u8,
-}
+    // leave the alternate screen so the score stays in the shell
+    drop(game);
 
-impl Scale {
-    pub fn new(x: u8, y: u8) -> Scale {
-        Scale {x, y}
-    }
-}
-
-struct CurrentQuiz {
-    pub question: String,
-    pub answers: Vec<String>,
-    pub correct_answer_id: u8,
-}
-
-impl CurrentQuiz {
-    pub fn new(
-        question: String,
-        answers: Vec<String>,
-        correct_answer_id: u8,
-    ) -> CurrentQuiz {
-        CurrentQuiz {
-            question,
-            answers,
-            correct_answer_id,
-        }
+    match result {
+        Ok(()) => println!("{}", score),
+        Err(err) => eprint!("{}", err),
     }
 }
 
-struct Game<R, W: Write> {
+struct Game<R, D: Renderer> {
     grid: grid::Grid,
     stdin: R,
-    stdout: W,
-    offset_x: u16,
-    offset_y: u16,
-    scale: Scale,
+    renderer: D,
 
     quizzes: Vec<Quiz>,
     quiz: Option<CurrentQuiz>,
-    quiz_ratio: u8,
+    trigger: Trigger,
+    mode: Mode,
+    scoring: Scoring,
+    /// Garbage rows owed for wrong answers, a row is added for each whole
+    /// one so that partly wrong answers add up.
+    penalty: f64,
+    deck: Deck,
+    strings: &'static Strings,
+    rng: StdRng,
 }
 
-impl<R: Read, W: Write> Game<R, W> {
-    fn new(stdin: R, stdout: W, term_width: u16, term_height: u16, scale: Scale, quiz_ratio: u8) -> Game<R, RawTerminal<W>> {
-        let grid = grid::Grid::new();
-
-        let quizzes: Vec<Quiz> = serde_yaml::from_str(QUIZZES).unwrap();
-
+impl<R: Read, D: Renderer> Game<R, D> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(stdin: R, renderer: D, quizzes: Vec<Quiz>, trigger: Trigger, mode: Mode, scoring: Scoring, deck: Deck, strings: &'static Strings, mut rng: StdRng) -> Game<R, D> {
         Game {
-            grid,
+            grid: grid::Grid::new(StdRng::seed_from_u64(rng.gen())),
             stdin,
-            stdout: stdout.into_raw_mode().unwrap(),
-            offset_x: (term_width - grid::WIDTH as u16 * scale.x as u16) / 2 - LAYOUT_QUIZ_WIDTH,
-            offset_y: term_height - grid::HEIGHT as u16 - 8,
-            scale,
+            renderer,
+            rng,
             quizzes,
             quiz: None,
-            quiz_ratio,
+            trigger,
+            mode,
+            scoring,
+            penalty: 0.0,
+            deck,
+            strings,
         }
     }
 
     fn run(&mut self) -> Result<()> {
-        write!(self.stdout, "{}{}{}{}", clear::All, style::Reset, cursor::Goto(1, 1), cursor::Hide)?;
-
-        self.draw_layout()?;
-        self.stdout.flush()?;
+        self.redraw()?;
 
-        let mut b: [u8; 3] = [0; 3];
-        let interval = time::Duration::from_millis(50);
-        'main: loop {
-            thread::sleep(interval);
+        // escape sequences such as PageUp take 4 bytes
+        let mut b: [u8; 4] = [0; 4];
+        loop {
+            thread::sleep(TICK);
 
-            if self.grid.on_new_tetromino {
-                self.grid.reset_on_new_tetromino();
-                self.quiz_rng();
+            if self.stdin.read(&mut b).is_err() {
+                b = [0; 4];
             }
 
-            // process input
-            if let Ok(_) = self.stdin.read(&mut b) {
-
-                match (b[0], b[1], b[2]) {
-                    // quit
-                    (b'\x1b', 0, 0) | (b'q', _, _) => break 'main,
-
-                    // answer quiz
-                    (n @ b'1'..=b'4', 0, 0) => self.answer(n),
-
-                    // play tetris
-                    (b'h', _, _) | ARROW_LEFT  if self.quiz.is_none() => self.grid.horizontal_move(-1),
-                    (b'l', _, _) | ARROW_RIGHT if self.quiz.is_none() => self.grid.horizontal_move(1),
-                    (b'k', _, _) | ARROW_UP    if self.quiz.is_none() => self.grid.rotate(),
-                    (b'j', _, _) | ARROW_DOWN  if self.quiz.is_none() => self.grid.fall(false),
-
-                    // reset game
-                    (b'r', _, _) => {
-                        self.quiz = None;
-                        self.grid.reset();
-                    }
-
-                    _ => (),
-                }
-
-                b[0] = 0;
-                b[1] = 0;
-                b[2] = 0;
+            if !self.update(&b) {
+                break;
             }
 
-            // update grid
-            self.grid.tick(interval);
-
-            // draw
-            self.draw_grid()?;
-
-            if self.grid.game_over {
-                self.draw_game_over()?;
-                self.quiz = None;
-            }
-
-            self.draw_quiz()?;
-            self.draw_status()?;
-
-            self.stdout.flush()?;
+            b = [0; 4];
+            self.draw()?;
         }
 
         Ok(())
     }
 
-    // quiz | tetris grid | status
-    fn draw_layout(&mut self) -> Result<()> {
-        for y in 1..(grid::HEIGHT * self.scale.y) + 2 {
-            let help_text = match y {
-                3 => "  ↑ / k: rotate",
-                4 => "  ← / h: move left",
-                5 => "  → / l: move right",
-                6 => "  ↓ / j: move down",
-                7 => "      r: reset",
-                _ => "",
-            };
-
-            let grid_width = grid::WIDTH * self.scale.x;
-            let x = self.offset_x + LAYOUT_QUIZ_WIDTH;
-            let y = self.offset_y + y as u16;
-            write!(self.stdout, "{}<!", cursor::Goto(x, y))?;
-
-            write!(self.stdout, "{}!>{}", cursor::Goto(x + grid_width as u16 + 2, y as u16), help_text)?;
+    /// Advances the game by one tick, given the key pressed during it or
+    /// zeros. Returns false once the player quits.
+    fn update(&mut self, b: &[u8; 4]) -> bool {
+        // wait for a bigger terminal, only quitting works meanwhile
+        if !self.renderer.fits() {
+            return !matches!(b[0], b'\x1b' | b'q');
         }
 
-        for x in 0..grid::WIDTH * self.scale.x {
-            let offset_x = self.offset_x + LAYOUT_QUIZ_WIDTH + x as u16 + 2;
-            let offset_y = self.offset_y + grid::HEIGHT as u16 * self.scale.y as u16 + 1;
-            write!(self.stdout, "{}*", cursor::Goto(offset_x as u16, offset_y as u16))?;
-
-            let offset_y = self.offset_y + grid::HEIGHT as u16 * self.scale.y as u16 + 1;
-            let c = if x % 2 == 0 { '\\' } else { '/' };
-            write!(self.stdout, "{}{}", cursor::Goto(offset_x as u16, (offset_y + 1) as u16), c)?;
+        if self.quiz.is_none() && !self.grid.game_over && self.trigger.fire(&self.grid, TICK, &mut self.rng) {
+            self.quiz_rng();
         }
+        self.grid.reset_events();
 
-        Ok(())
-    }
+        // process input
+        let typing = self.quiz.as_ref().is_some_and(|quiz| quiz.kind == Kind::FreeText);
 
-    fn draw_grid(&mut self) -> Result<()> {
-        let offset_x = self.offset_x + LAYOUT_QUIZ_WIDTH + 2;
+        match (b[0], b[1], b[2]) {
+            // quit
+            (b'\x1b', 0, 0) => return false,
 
-        for i in 0..self.grid.cells.len() {
-            let y = i as u8 / grid::WIDTH + 1;
-            let x = i as u8 % grid::WIDTH;
+            // answer quiz
+            (key, 0, 0) if self.quiz.as_ref().is_some_and(|quiz| quiz.answer_id(key).is_some()) => self.answer(key),
+            (b'\r', 0, 0) if self.quiz.as_ref().is_some_and(|quiz| quiz.submits_on_enter()) => self.submit(),
+            (BACKSPACE, 0, 0) if typing => self.erase(),
 
-            let x = offset_x + x as u16 * self.scale.x as u16;
-            let y = self.offset_y + y as u16;
-            let c = if self.grid.cells[i] { "[]" } else { " ." };
+            // scroll quiz
+            PAGE_UP if self.quiz.is_some() => self.scroll_quiz(-1),
+            PAGE_DOWN if self.quiz.is_some() => self.scroll_quiz(1),
 
-            write!(self.stdout, "{}{}", cursor::Goto(x as u16, y as u16), c)?;
-        }
+            // arrow keys still reach the board in juggle mode
+            _ if typing && b[0] != b'\x1b' => self.type_answer(b),
+            (b'[', 0, 0) if self.quiz.is_some() => self.scroll_quiz(-1),
+            (b']', 0, 0) if self.quiz.is_some() => self.scroll_quiz(1),
 
-        if self.quiz.is_some() {
-            self.draw_quiz_lock()?;
-        }
+            // quit
+            (b'q', _, _) => return false,
 
-        Ok(())
-    }
+            // play tetris
+            (b'h', _, _) | ARROW_LEFT  if self.can_play() => self.grid.horizontal_move(-1),
+            (b'l', _, _) | ARROW_RIGHT if self.can_play() => self.grid.horizontal_move(1),
+            (b'k', _, _) | ARROW_UP    if self.can_play() => self.grid.rotate(),
+            (b'j', _, _) | ARROW_DOWN  if self.can_play() => self.grid.fall(false),
 
-    fn draw_quiz_lock(&mut self) -> Result<()> {
-        let x = LAYOUT_QUIZ_WIDTH + 2;
-        let y = 1;
+            // reset game
+            (b'r', _, _) => {
+                self.quiz = None;
+                self.penalty = 0.0;
+                self.grid.reset();
+            }
 
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 3), "╭──────────╮")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 4), "   locked   ")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 5), "╰──────────╯")?;
+            _ => (),
+        }
 
-        Ok(())
-    }
+        // update grid
+        if self.quiz.is_none() || self.mode != Mode::Freeze {
+            self.grid.tick(TICK);
+        }
 
-    fn draw_game_over(&mut self) -> Result<()> {
-        let x = LAYOUT_QUIZ_WIDTH + 2;
-        let y = 1;
+        if self.grid.game_over {
+            self.quiz = None;
+        }
 
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 3), "╭──────────╮")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 4), "  G A M E   ")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 5), "            ")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 6), "   O V E R  ")?;
-        write!(self.stdout, "{}{}", cursor::Goto(x + self.offset_x + 4, y + self.offset_y + 7), "╰──────────╯")?;
+        true
+    }
 
-        Ok(())
+    fn redraw(&mut self) -> Result<()> {
+        let view = View { grid: &self.grid, quiz: self.quiz.as_ref(), mode: self.mode };
+        self.renderer.redraw(&view)
     }
 
-    fn draw_status(&mut self) -> Result<()> {
-        let grid_width = grid::WIDTH * self.scale.x;
-        let offset_x = self.offset_x + LAYOUT_QUIZ_WIDTH + grid_width as u16 + 6;
-        let offset_y = self.offset_y + grid::HEIGHT as u16 * self.scale.y as u16;
+    fn draw(&mut self) -> Result<()> {
+        let view = View { grid: &self.grid, quiz: self.quiz.as_ref(), mode: self.mode };
+        self.renderer.draw(&view)
+    }
 
-        write!(self.stdout, "{}[{}{: >8}]", cursor::Goto(offset_x as u16, (offset_y - 4) as u16), "SCORE: ", self.grid.score)?;
-        write!(self.stdout, "{}[{}{: >8}]", cursor::Goto(offset_x as u16, (offset_y - 2) as u16), "LEVEL: ", self.grid.level)?;
-        write!(self.stdout, "{}[{}{: >8}]", cursor::Goto(offset_x as u16, (offset_y - 0) as u16), "LINES: ", self.grid.cleared)?;
+    /// Score, level and lines, as printed after quitting.
+    fn final_score(&self) -> String {
+        format!(
+            "{} {}  {} {}  {} {}",
+            self.strings.score, self.grid.score,
+            self.strings.level, self.grid.level,
+            self.strings.lines, self.grid.cleared,
+        )
+    }
 
-        Ok(())
+    /// Whether the player can move pieces.
+    fn can_play(&self) -> bool {
+        self.quiz.is_none() || self.mode == Mode::Juggle
     }
 
     fn quiz_rng(&mut self) {
@@ -278,115 +436,134 @@ impl<R: Read, W: Write> Game<R, W> {
             return;
         }
 
-        if !thread_rng().gen_ratio(self.quiz_ratio as u32, 100) {
-            return;
-        }
-
-        let id = thread_rng().gen_range(0..self.quizzes.len());
+        let weights: Vec<f64> = self.quizzes.iter().map(|quiz| quiz.weight).collect();
+        let id = self.deck.draw(&weights, &mut self.rng);
         let quiz = &self.quizzes[id];
 
-        let mut answers = quiz.wrong_answers.clone();
-        answers.shuffle(&mut thread_rng());
-
-        let correct_answer_id = thread_rng().gen_range(0..=answers.len());
-        answers.insert(correct_answer_id, quiz.answer.clone());
-
-        self.quiz = Some(CurrentQuiz::new(
-            quiz.question.clone(),
-            answers,
-            correct_answer_id as u8,
-        ));
+        self.quiz = Some(CurrentQuiz::from_quiz(quiz, self.strings, &mut self.rng));
     }
 
-    fn answer(&mut self, n: u8) {
-        if let Some(quiz) = &self.quiz {
-            if n - 48 != quiz.correct_answer_id + 1 {
-                self.grid.punish();
+    fn answer(&mut self, key: u8) {
+        if let Some(quiz) = &mut self.quiz {
+            if let Some(id) = quiz.answer_id(key) {
+                if quiz.kind == Kind::MultiSelect {
+                    quiz.toggle(id);
+                    return;
+                }
+
+                quiz.selected = vec![id];
             }
         }
 
-        self.quiz = None;
+        self.submit();
     }
 
-    fn draw_quiz(&mut self) -> Result<()> {
-        if let Some(quiz) = &self.quiz {
-
-            // draw questoin
-            let line_width = LAYOUT_QUIZ_WIDTH - 4;
-            let offset_x = self.offset_x + 2;
-            let offset_y = self.offset_y + 3;
+    fn type_answer(&mut self, b: &[u8]) {
+        let len = b.iter().position(|&b| b == 0).unwrap_or(b.len());
 
-            let lines = split_into_lines(&quiz.question, line_width);
-            for i in 0..lines.len() {
-                write!(self.stdout, "{}{}", cursor::Goto(offset_x, offset_y + i as u16), lines[i])?;
-            }
-
-            // draw answer
-            let line_width = LAYOUT_QUIZ_WIDTH - 7;
-            let mut offset_y = self.offset_y + lines.len() as u16 + 4;
+        if let (Some(quiz), Ok(text)) = (&mut self.quiz, std::str::from_utf8(&b[..len])) {
+            quiz.type_text(text);
+        }
+    }
 
-            for i in 0..quiz.answers.len() {
-                let lines = split_into_lines(&quiz.answers[i], line_width);
-                write!(self.stdout, "{}{}. ", cursor::Goto(offset_x, offset_y as u16), i + 1)?;
+    fn erase(&mut self) {
+        if let Some(quiz) = &mut self.quiz {
+            quiz.input.pop();
+        }
+    }
 
-                for j in 0..lines.len() {
-                    write!(self.stdout, "{}{}", cursor::Goto(offset_x + 3, offset_y as u16), lines[j])?;
-                    offset_y = offset_y + 1;
-                }
+    fn submit(&mut self) {
+        if let Some(quiz) = self.quiz.take() {
+            self.penalty += 1.0 - quiz.credit(self.scoring);
 
-                offset_y = offset_y + 1;
+            // shares such as 0.2 do not add up to exactly 1
+            while self.penalty > 1.0 - PENALTY_EPSILON {
+                self.penalty -= 1.0;
+                self.grid.punish();
             }
+        }
+    }
 
-            Ok(())
-        } else {
-            self.clear_area(0, 0, LAYOUT_QUIZ_WIDTH, grid::HEIGHT as u16 + 10)
+    fn scroll_quiz(&mut self, pages: i16) {
+        if let Some(quiz) = &mut self.quiz {
+            quiz.scroll_by(pages, LAYOUT_QUIZ_WIDTH - 4, LAYOUT_QUIZ_HEIGHT);
         }
     }
+}
 
-    fn clear_area(&mut self, from_x: u16, from_y: u16, width: u16, height: u16) -> Result<()> {
-        let offset_x = self.offset_x + from_x;
-        let offset_y = self.offset_y + from_y;
+/// Loads quiz packs from `files`, or the built-in pack if there are none,
+/// in `language`. The weight of each pack, or its override in
+/// `pack_weights`, is folded into the weight of its quizzes.
+fn load_quizzes(files: &[PathBuf], pack_weights: &[(String, f64)], language: &str) -> std::result::Result<Vec<Quiz>, String> {
+    let mut packs = Vec::new();
 
-        let spaces = " ".repeat(width as usize);
+    if files.is_empty() {
+        let mut pack = Pack::parse(QUIZZES).map_err(|err| format!("{}: {}", BUILTIN_PACK, err))?;
+        pack.name = BUILTIN_PACK.to_string();
+        packs.push(pack);
+    }
 
-        for y in offset_y..offset_y + height {
-            write!(self.stdout, "{}{}", cursor::Goto(offset_x, y), spaces)?;
+    for file in files.iter() {
+        packs.push(import::load(file).map_err(|err| format!("{}: {}", file.display(), err))?);
+    }
+
+    let mut names = Vec::new();
+    let mut quizzes = Vec::new();
+
+    for pack in packs {
+        let weight = pack_weights.iter().rev()
+            .find(|(name, _)| *name == pack.name)
+            .map_or(pack.weight, |(_, weight)| *weight);
+        quiz::validate_weight(weight).map_err(|err| format!("pack {}: {}", pack.name, err))?;
+
+        for quiz in pack.quizzes {
+            let mut quiz = quiz.localize(language);
+            quiz.validate().map_err(|err| format!("pack {}: quiz {:?} {}", pack.name, quiz.question, err))?;
+            quiz.weight *= weight;
+            quizzes.push(quiz);
         }
 
-        Ok(())
+        names.push(pack.name);
     }
-}
 
-impl<R, W: Write> Drop for Game<R, W> {
-    fn drop(&mut self) {
-        write!(self.stdout, "{}{}", style::Reset, cursor::Show).unwrap();
+    if let Some((name, _)) = pack_weights.iter().find(|(name, _)| !names.contains(name)) {
+        return Err(format!("no quiz pack named {}", name));
     }
+
+    if !quizzes.iter().any(|quiz| quiz.weight > 0.0) {
+        return Err("no quiz has a positive weight".to_string());
+    }
+
+    Ok(quizzes)
 }
 
-fn split_into_lines(text: &String, width: u16) -> Vec<String> {
-    let mut lines = Vec::with_capacity(text.len() / width as usize + 1);
-    let mut iter = text.split_whitespace();
+/// Writes the quiz pack in `file` out as YAML, warning about quizzes that
+/// will need fixing before they can be played.
+fn convert(file: &Path, from: Option<import::Format>, output: Option<&Path>) -> std::result::Result<(), String> {
+    let pack = match from {
+        Some(format) => import::load_as(file, format)?,
+        None => import::load(file)?,
+    };
 
-    let mut line = String::with_capacity(width as usize);
-    loop {
-        if let Some(word) = iter.next() {
-            if line.len() + word.len() + 1 > width as usize {
-                lines.push(line.to_string());
-                line = String::with_capacity(width as usize);
-            }
+    let yaml = serde_yaml::to_string(&pack).map_err(|err| err.to_string())?;
 
-            if line.len() > 0 {
-                line = line + " " + word;
-            } else {
-                line = line + word;
-            }
-        } else {
-            if line.len() > 0 {
-                lines.push(line.to_string());
-            }
-            break;
-        }
+    match output {
+        Some(output) => fs::write(output, yaml).map_err(|err| format!("{}: {}", output.display(), err))?,
+        None => print!("{}", yaml),
+    }
+
+    let name = file.display().to_string();
+    for problem in lint::lint_pack(&pack, &[], &name, &mut HashMap::new()) {
+        eprintln!("{}: warning: {}", name, problem.message);
     }
 
-    return lines;
+    Ok(())
+}
+
+fn parse_pack_weight(arg: &str) -> std::result::Result<(String, f64), String> {
+    let (name, weight) = arg.split_once('=').ok_or("expected PACK=WEIGHT")?;
+    let weight: f64 = weight.parse().map_err(|_| format!("invalid weight {:?}", weight))?;
+    quiz::validate_weight(weight)?;
+
+    Ok((name.to_string(), weight))
 }
@@ -0,0 +1,208 @@
+use crate::text;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+}
+
+impl Style {
+    pub fn code() -> Style {
+        Style { code: true, ..Style::default() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn new(text: String, style: Style) -> Span {
+        Span { text, style }
+    }
+
+    pub fn plain(text: String) -> Span {
+        Span::new(text, Style::default())
+    }
+}
+
+/// Parses `**bold**`, `_italic_` and `` `code` `` into styled spans.
+/// Markers without a closing counterpart are kept as text, and so are
+/// underscores inside words such as `snake_case`.
+pub fn parse(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut spans = Vec::new();
+    let mut span = String::new();
+    let mut style = Style::default();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i + 1..];
+
+        let mut next = style;
+        let mut skip = 1;
+
+        if c == '`' && (style.code || rest.contains(&'`')) {
+            next.code = !style.code;
+        } else if !style.code && c == '*' && rest.first() == Some(&'*') && (style.bold || has_bold_end(&rest[1..])) {
+            next.bold = !style.bold;
+            skip = 2;
+        } else if !style.code && c == '_' {
+            let prev = i.checked_sub(1).map(|j| chars[j]);
+            let after = rest.first().copied();
+
+            if style.italic && is_italic_end(prev, after) {
+                next.italic = false;
+            } else if !style.italic && is_italic_start(prev, after) && has_italic_end(rest) {
+                next.italic = true;
+            }
+        }
+
+        if next == style {
+            span.push(c);
+        } else {
+            if !span.is_empty() {
+                spans.push(Span::new(std::mem::take(&mut span), style));
+            }
+            style = next;
+            i += skip - 1;
+        }
+
+        i += 1;
+    }
+
+    if !span.is_empty() {
+        spans.push(Span::new(span, style));
+    }
+
+    spans
+}
+
+/// The text without markup.
+pub fn plain(text: &str) -> String {
+    parse(text).into_iter().map(|span| span.text).collect()
+}
+
+/// Wraps `text` with `text::wrap`, measuring only the
+/// characters left once markup is parsed.
+pub fn wrap(text: &str, width: u16) -> Vec<Vec<Span>> {
+    let spans = parse(text);
+    let pieces: Vec<(&str, Style)> = spans.iter().map(|span| (span.text.as_str(), span.style)).collect();
+
+    text::wrap(&pieces, width)
+        .into_iter()
+        .map(|line| line.into_iter().map(|(text, style)| Span::new(text, style)).collect())
+        .collect()
+}
+
+fn has_bold_end(rest: &[char]) -> bool {
+    rest.windows(2).any(|pair| pair == ['*', '*'])
+}
+
+// blanks to fill in such as `____` are never italic
+fn is_italic_start(prev: Option<char>, after: Option<char>) -> bool {
+    !prev.is_some_and(|c| c.is_alphanumeric() || c == '_') && after.is_some_and(|c| !c.is_whitespace() && c != '_')
+}
+
+fn is_italic_end(prev: Option<char>, after: Option<char>) -> bool {
+    prev.is_some_and(|c| !c.is_whitespace() && c != '_') && !after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn has_italic_end(rest: &[char]) -> bool {
+    (1..rest.len()).any(|j| rest[j] == '_' && is_italic_end(Some(rest[j - 1]), rest.get(j + 1).copied()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bold() -> Style {
+        Style { bold: true, ..Style::default() }
+    }
+
+    fn italic() -> Style {
+        Style { italic: true, ..Style::default() }
+    }
+
+    fn span(text: &str, style: Style) -> Span {
+        Span::new(text.to_string(), style)
+    }
+
+    #[test]
+    fn plain_text_is_one_span() {
+        assert_eq!(parse("Which reader supports chip?"), vec![span("Which reader supports chip?", Style::default())]);
+    }
+
+    #[test]
+    fn parses_bold_italic_and_code() {
+        assert_eq!(parse("Which is **NOT** an _acquirer_ in `reader-payment`?"), vec![
+            span("Which is ", Style::default()),
+            span("NOT", bold()),
+            span(" an ", Style::default()),
+            span("acquirer", italic()),
+            span(" in ", Style::default()),
+            span("reader-payment", Style::code()),
+            span("?", Style::default()),
+        ]);
+    }
+
+    #[test]
+    fn nests_italic_in_bold() {
+        assert_eq!(parse("**very _much_**"), vec![
+            span("very ", bold()),
+            span("much", Style { bold: true, italic: true, code: false }),
+        ]);
+    }
+
+    #[test]
+    fn keeps_markup_inside_code() {
+        assert_eq!(parse("`**x** _y_`"), vec![span("**x** _y_", Style::code())]);
+    }
+
+    #[test]
+    fn keeps_unclosed_markers() {
+        assert_eq!(plain("2 ** 3 and `tick and _under"), "2 ** 3 and `tick and _under");
+    }
+
+    #[test]
+    fn keeps_underscores_inside_words() {
+        assert_eq!(plain("snake_case and MAX_VALUE_SIZE"), "snake_case and MAX_VALUE_SIZE");
+        assert_eq!(parse("a _b_, c"), vec![
+            span("a ", Style::default()),
+            span("b", italic()),
+            span(", c", Style::default()),
+        ]);
+    }
+
+    #[test]
+    fn keeps_blanks_made_of_underscores() {
+        assert_eq!(plain("PayPal acquired iZettle in ____."), "PayPal acquired iZettle in ____.");
+        assert_eq!(plain("fill _ in _ twice"), "fill _ in _ twice");
+    }
+
+    #[test]
+    fn wraps_by_visible_characters() {
+        let lines = wrap("**Which** one is _not_ `ours`?", 12);
+
+        assert_eq!(lines, vec![
+            vec![span("Which", bold()), span(" one is", Style::default())],
+            vec![span("not", italic()), span(" ", Style::default()), span("ours", Style::code()), span("?", Style::default())],
+        ]);
+    }
+
+    #[test]
+    fn keeps_style_of_spaces_inside_code() {
+        let lines = wrap("run `cargo test` now", 20);
+
+        assert_eq!(lines, vec![vec![
+            span("run ", Style::default()),
+            span("cargo test", Style::code()),
+            span(" now", Style::default()),
+        ]]);
+    }
+}
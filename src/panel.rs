@@ -0,0 +1,326 @@
+use std::io::{Write, Result};
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use termion::{cursor, style};
+
+use crate::locale::Strings;
+use crate::markup::{self, Span, Style};
+use crate::quiz::{self, Kind, Quiz, Scoring};
+use crate::text;
+
+/// Columns a tab in a code block expands to.
+const TAB_WIDTH: usize = 4;
+
+/// Most characters a free-text answer may take.
+const MAX_INPUT_LENGTH: usize = 64;
+
+/// How code blocks in quizzes are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum CodeStyle {
+    Plain,
+    Bold,
+    #[default]
+    Invert,
+}
+
+/// One row of the quiz panel.
+pub type Line = Vec<Span>;
+
+pub struct CurrentQuiz {
+    pub kind: Kind,
+    pub question: String,
+    pub code: String,
+    pub answers: Vec<String>,
+    pub correct_answer_ids: Vec<u8>,
+    pub selected: Vec<u8>,
+    pub accepted_answers: Vec<String>,
+    pub input: String,
+    pub scroll: u16,
+    pub strings: &'static Strings,
+}
+
+impl CurrentQuiz {
+    pub fn new(
+        kind: Kind,
+        question: String,
+        answers: Vec<String>,
+        correct_answer_ids: Vec<u8>,
+        strings: &'static Strings,
+    ) -> CurrentQuiz {
+        CurrentQuiz {
+            kind,
+            question,
+            code: String::new(),
+            answers,
+            correct_answer_ids,
+            selected: Vec::new(),
+            accepted_answers: Vec::new(),
+            input: String::new(),
+            scroll: 0,
+            strings,
+        }
+    }
+
+    pub fn from_quiz(quiz: &Quiz, strings: &'static Strings, rng: &mut impl Rng) -> CurrentQuiz {
+        let (answers, correct_answer_ids) = quiz_answers(quiz, strings, rng);
+
+        let mut current = CurrentQuiz::new(
+            quiz.kind,
+            quiz.question.to_string(),
+            answers,
+            correct_answer_ids,
+            strings,
+        );
+
+        current.code = quiz.code.clone();
+
+        if quiz.kind == Kind::FreeText {
+            current.accepted_answers = std::iter::once(&quiz.answer)
+                .chain(quiz.alternatives.iter())
+                .map(|answer| quiz::normalize(&markup::plain(answer)))
+                .collect();
+        }
+
+        current
+    }
+
+    /// Appends typed text to the free-text input.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if self.input.chars().count() >= MAX_INPUT_LENGTH {
+                break;
+            }
+
+            self.input.push(c);
+        }
+    }
+
+    pub fn toggle(&mut self, id: u8) {
+        if let Some(i) = self.selected.iter().position(|&selected| selected == id) {
+            self.selected.remove(i);
+        } else {
+            self.selected.push(id);
+        }
+    }
+
+    /// How right the answer is, from 0 to 1. Only multi-select answers
+    /// can be partly right, and only with partial scoring.
+    pub fn credit(&self, scoring: Scoring) -> f64 {
+        if self.kind == Kind::FreeText {
+            return if self.accepted_answers.contains(&quiz::normalize(&self.input)) { 1.0 } else { 0.0 };
+        }
+
+        let judged = (0..self.answers.len() as u8)
+            .filter(|id| self.selected.contains(id) == self.correct_answer_ids.contains(id))
+            .count();
+
+        match (self.kind, scoring) {
+            (Kind::MultiSelect, Scoring::Partial) => judged as f64 / self.answers.len() as f64,
+            _ if judged == self.answers.len() => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Whether Enter submits the answer. Other quizzes are answered by
+    /// the key of an option alone.
+    pub fn submits_on_enter(&self) -> bool {
+        matches!(self.kind, Kind::MultiSelect | Kind::FreeText)
+    }
+
+    /// The answer picked by `key`, if the key is valid for this quiz.
+    pub fn answer_id(&self, key: u8) -> Option<u8> {
+        let keys = &quiz::ANSWER_KEYS[..self.answers.len()];
+        let id = keys.iter().position(|&k| k == key).or_else(|| match self.kind {
+            Kind::TrueFalse => quiz::TRUE_FALSE_KEYS.iter().position(|&k| k == key),
+            _ => None,
+        });
+
+        id.map(|id| id as u8)
+    }
+
+    /// Scrolls a panel of `width` by `height` by whole pages, keeping a
+    /// line of context.
+    pub fn scroll_by(&mut self, pages: i16, width: u16, height: u16) {
+        let page = height as i16 - 1;
+        let max = self.lines(width).len().saturating_sub(height as usize) as i16;
+
+        self.scroll = (self.scroll as i16 + pages * page).clamp(0, max) as u16;
+    }
+
+    pub fn label(&self, id: usize) -> char {
+        match self.kind {
+            Kind::TrueFalse => quiz::TRUE_FALSE_KEYS[id].to_ascii_uppercase() as char,
+            _ => quiz::ANSWER_KEYS[id] as char,
+        }
+    }
+
+    /// Content of a quiz panel `line_width` columns wide, one entry per row.
+    pub fn lines(&self, line_width: u16) -> Vec<Line> {
+        let mut lines: Vec<Line> = markup::wrap(&self.question, line_width);
+        lines.push(Line::new());
+
+        if !self.code.is_empty() {
+            for line in code_lines(&self.code) {
+                lines.push(vec![Span::new(text::truncate(&line, line_width as usize), Style::code())]);
+            }
+
+            lines.push(Line::new());
+        }
+
+        if self.kind == Kind::FreeText {
+            // keep the end of the input visible
+            let mut visible = self.input.as_str();
+            while !visible.is_empty() && text::width(visible) + 3 > line_width as usize {
+                let mut chars = visible.chars();
+                chars.next();
+                visible = chars.as_str();
+            }
+
+            lines.push(vec![Span::plain(format!("> {}_", visible))]);
+            lines.push(Line::new());
+            lines.push(vec![Span::plain(format!("⏎: {}", self.strings.confirm))]);
+
+            return lines;
+        }
+
+        let indent = answer_indent(self.kind);
+
+        for (i, answer) in self.answers.iter().enumerate() {
+            let mut prefix = format!("{}. ", self.label(i));
+
+            if self.kind == Kind::MultiSelect {
+                let mark = if self.selected.contains(&(i as u8)) { 'x' } else { ' ' };
+                prefix = format!("{}[{}] ", prefix, mark);
+            }
+
+            for line in markup::wrap(answer, line_width.saturating_sub(indent)) {
+                let mut spans = vec![Span::plain(prefix)];
+                spans.extend(line);
+                lines.push(spans);
+
+                prefix = " ".repeat(indent as usize);
+            }
+
+            lines.push(Line::new());
+        }
+
+        if self.kind == Kind::MultiSelect {
+            lines.push(vec![Span::plain(format!("⏎: {}", self.strings.confirm))]);
+        } else {
+            lines.pop();
+        }
+
+        lines
+    }
+}
+
+/// Lines of a code block with tabs expanded and trailing newlines dropped.
+pub fn code_lines(code: &str) -> Vec<String> {
+    code.trim_end_matches('\n')
+        .lines()
+        .map(|line| line.trim_end().replace('\t', &" ".repeat(TAB_WIDTH)))
+        .collect()
+}
+
+/// Options of `quiz` in display order, and the indices of the correct ones.
+fn quiz_answers(quiz: &Quiz, strings: &Strings, rng: &mut impl Rng) -> (Vec<String>, Vec<u8>) {
+    match quiz.kind {
+        Kind::Choice => {
+            let mut answers: Vec<String> = quiz.wrong_answers.iter().map(|answer| answer.to_string()).collect();
+            answers.shuffle(rng);
+
+            let correct_answer_id = rng.gen_range(0..=answers.len());
+            answers.insert(correct_answer_id, quiz.answer.to_string());
+
+            (answers, vec![correct_answer_id as u8])
+        }
+
+        Kind::TrueFalse => {
+            let answers = vec![strings.true_.to_string(), strings.false_.to_string()];
+            let correct_answer_id = if quiz.answer.parse() == Ok(true) { 0 } else { 1 };
+
+            (answers, vec![correct_answer_id])
+        }
+
+        Kind::MultiSelect => {
+            let mut options: Vec<(&quiz::Text, bool)> = quiz.answers.iter().map(|answer| (answer, true))
+                .chain(quiz.wrong_answers.iter().map(|answer| (answer, false)))
+                .collect();
+            options.shuffle(rng);
+
+            let answers = options.iter().map(|(answer, _)| answer.to_string()).collect();
+            let correct_answer_ids = (0..options.len() as u8).filter(|&id| options[id as usize].1).collect();
+
+            (answers, correct_answer_ids)
+        }
+
+        Kind::FreeText => (Vec::new(), Vec::new()),
+    }
+}
+
+/// Columns taken by the label (and checkbox) in front of an answer.
+fn answer_indent(kind: Kind) -> u16 {
+    match kind {
+        Kind::MultiSelect => 7,
+        _ => 3,
+    }
+}
+
+/// Draws `quiz` in a panel `width` columns wide with `height` rows of
+/// content starting at `x`, `y`, and scroll hints on the rows just above
+/// and below.
+pub fn draw_quiz_panel<W: Write>(stdout: &mut W, quiz: &CurrentQuiz, x: u16, y: u16, width: u16, height: u16, code_style: CodeStyle) -> Result<()> {
+    for (row, line) in quiz_panel(quiz, width, height).iter().enumerate() {
+        write!(stdout, "{}", cursor::Goto(x, y - 1 + row as u16))?;
+
+        let mut line_width = 0;
+        for span in line.iter() {
+            write_style(stdout, span.style, code_style)?;
+            write!(stdout, "{}{}", span.text, style::Reset)?;
+            line_width += text::width(&span.text);
+        }
+
+        write!(stdout, "{}", " ".repeat((width as usize).saturating_sub(line_width)))?;
+    }
+
+    Ok(())
+}
+
+/// Rows of a quiz panel `width` columns wide with `height` rows of
+/// content: a scroll hint, the visible content and another scroll hint.
+pub fn quiz_panel(quiz: &CurrentQuiz, width: u16, height: u16) -> Vec<Line> {
+    let lines = quiz.lines(width);
+    let scroll = quiz.scroll as usize;
+    let more_above = scroll > 0;
+    let more_below = lines.len() > scroll + height as usize;
+
+    let above = if more_above { "▲ [ / PgUp" } else { "" };
+    let below = if more_below { "▼ ] / PgDn" } else { "" };
+
+    let mut panel = vec![vec![Span::plain(above.to_string())]];
+    panel.extend((0..height as usize).map(|row| lines.get(scroll + row).cloned().unwrap_or_default()));
+    panel.push(vec![Span::plain(below.to_string())]);
+
+    panel
+}
+
+fn write_style<W: Write>(stdout: &mut W, span_style: Style, code_style: CodeStyle) -> Result<()> {
+    if span_style.bold {
+        write!(stdout, "{}", style::Bold)?;
+    }
+
+    if span_style.italic {
+        write!(stdout, "{}", style::Italic)?;
+    }
+
+    if span_style.code {
+        match code_style {
+            CodeStyle::Plain => (),
+            CodeStyle::Bold => write!(stdout, "{}", style::Bold)?,
+            CodeStyle::Invert => write!(stdout, "{}", style::Invert)?,
+        }
+    }
+
+    Ok(())
+}
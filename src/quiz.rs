@@ -1,8 +1,389 @@
-use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Deref;
+
+use serde::{Serialize, Deserialize, Deserializer};
+use serde::de::{self, MapAccess, Visitor};
+
+use crate::locale::DEFAULT_LANGUAGE;
+
+/// Answer keys, in the order options are shown in the quiz panel: digits,
+/// then letters other than t/f of true/false quizzes, q to quit, r to
+/// reset and hjkl to move.
+pub const ANSWER_KEYS: &[u8] = b"123456789abcdegimnopsuvwxyz";
+
+/// Keys accepted for true/false quizzes, in addition to `1` and `2`.
+pub const TRUE_FALSE_KEYS: &[u8] = b"tf";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// One correct `answer` shuffled among `wrong_answers`.
+    #[default]
+    Choice,
+
+    /// `answer` is either `true` or `false`, no `wrong_answers`.
+    TrueFalse,
+
+    /// Several correct `answers` shuffled among `wrong_answers`, the
+    /// player ticks every correct one and confirms.
+    MultiSelect,
+
+    /// The player types the `answer`, or one of the `alternatives`.
+    FreeText,
+}
+
+/// How a multi-select answer is judged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Scoring {
+    /// Every option must be ticked or left blank correctly.
+    #[default]
+    AllOrNothing,
+
+    /// Each option ticked or left blank correctly earns its share of the
+    /// credit.
+    Partial,
+}
+
+/// How an open quiz affects the game.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Mode {
+    /// Pieces cannot be moved but keep falling.
+    #[default]
+    Lock,
+
+    /// Pieces keep falling and can still be moved.
+    Juggle,
+
+    /// Pieces stop falling until the quiz is answered.
+    Freeze,
+}
+
+/// Quiz text, either the same in every language or one variant per
+/// language code such as `{en: Yes, sv: Ja}`. Derefs to the English
+/// variant, or the first one if there is no English.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Text {
+    Plain(String),
+    Translated(BTreeMap<String, String>),
+}
+
+impl Text {
+    /// The variant for `language`, falling back to English.
+    pub fn get(&self, language: &str) -> &str {
+        match self {
+            Text::Plain(text) => text,
+            Text::Translated(variants) => variants.get(language).map_or(&**self, |text| text),
+        }
+    }
+
+    /// Languages with their own variant, none if the text is plain.
+    pub fn languages(&self) -> Vec<&str> {
+        match self {
+            Text::Plain(_) => Vec::new(),
+            Text::Translated(variants) => variants.keys().map(|language| language.as_str()).collect(),
+        }
+    }
+
+    /// Replaces the variant for `language`, or the whole text if it is
+    /// the same in every language.
+    pub fn set(&mut self, language: &str, text: String) {
+        match self {
+            Text::Plain(_) => *self = Text::Plain(text),
+            Text::Translated(variants) => {
+                variants.insert(language.to_string(), text);
+            }
+        }
+    }
+
+    fn localize(&self, language: &str) -> Text {
+        Text::Plain(self.get(language).to_string())
+    }
+}
+
+// scalars such as `true` or `2018` are read as text, like they would be
+// for a `String`
+impl<'de> Deserialize<'de> for Text {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Text, D::Error> {
+        deserializer.deserialize_any(TextVisitor)
+    }
+}
+
+struct TextVisitor;
+
+impl<'de> Visitor<'de> for TextVisitor {
+    type Value = Text;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string or a map from language codes to strings")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Text, E> {
+        Ok(Text::from(text))
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Text, E> {
+        Ok(Text::Plain(value.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Text, E> {
+        Ok(Text::Plain(value.to_string()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Text, E> {
+        Ok(Text::Plain(value.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Text, E> {
+        Ok(Text::Plain(value.to_string()))
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<Text, M::Error> {
+        BTreeMap::deserialize(de::value::MapAccessDeserializer::new(map)).map(Text::Translated)
+    }
+}
+
+impl Default for Text {
+    fn default() -> Text {
+        Text::Plain(String::new())
+    }
+}
+
+impl Deref for Text {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            Text::Plain(text) => text,
+            Text::Translated(variants) => variants.get(DEFAULT_LANGUAGE)
+                .or_else(|| variants.values().next())
+                .map_or("", |text| text),
+        }
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Text {
+        Text::Plain(text)
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Text {
+        Text::Plain(text.to_string())
+    }
+}
+
+impl PartialEq<&str> for Text {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self)
+    }
+}
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Quiz {
-    pub question: String,
-    pub answer: String,
-    pub wrong_answers: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub kind: Kind,
+    pub question: Text,
+    /// Shown verbatim below the question, keeping indentation and newlines.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub answer: Text,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<Text>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrong_answers: Vec<Text>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Text>,
+    /// How often the quiz is picked relative to others, 0 never picks it.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub weight: f64,
+}
+
+/// A quiz pack file: either just a list of quizzes, or a mapping with a
+/// `name`, a `weight` applied to all its quizzes and the `quizzes`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pack {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub weight: f64,
+    pub quizzes: Vec<Quiz>,
+}
+
+impl Pack {
+    pub fn new(quizzes: Vec<Quiz>) -> Pack {
+        Pack {
+            name: String::new(),
+            weight: 1.0,
+            quizzes,
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Pack, serde_yaml::Error> {
+        let value: serde_yaml::Value = serde_yaml::from_str(source)?;
+
+        // parse again from source rather than from `value` to keep line
+        // numbers in errors
+        if value.is_sequence() {
+            Ok(Pack::new(serde_yaml::from_str(source)?))
+        } else {
+            serde_yaml::from_str(source)
+        }
+    }
+}
+
+impl Quiz {
+    pub fn new(question: String, answer: String) -> Quiz {
+        Quiz {
+            kind: Kind::Choice,
+            question: Text::from(question),
+            code: String::new(),
+            answer: Text::from(answer),
+            answers: Vec::new(),
+            wrong_answers: Vec::new(),
+            alternatives: Vec::new(),
+            weight: 1.0,
+        }
+    }
+
+    /// Languages any text of the quiz is translated to.
+    pub fn languages(&self) -> Vec<&str> {
+        let mut languages: Vec<&str> = self.texts().flat_map(|text| text.languages()).collect();
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    /// Every text of the quiz.
+    pub fn texts(&self) -> impl Iterator<Item = &Text> {
+        std::iter::once(&self.question)
+            .chain(std::iter::once(&self.answer))
+            .chain(self.answers.iter())
+            .chain(self.wrong_answers.iter())
+            .chain(self.alternatives.iter())
+    }
+
+    /// The quiz with every text in `language`, or English where there is
+    /// no variant in that language.
+    pub fn localize(&self, language: &str) -> Quiz {
+        let localize = |texts: &[Text]| texts.iter().map(|text| text.localize(language)).collect();
+
+        Quiz {
+            kind: self.kind,
+            question: self.question.localize(language),
+            code: self.code.clone(),
+            answer: self.answer.localize(language),
+            answers: localize(&self.answers),
+            wrong_answers: localize(&self.wrong_answers),
+            alternatives: localize(&self.alternatives),
+            weight: self.weight,
+        }
+    }
+
+    /// Number of options the player picks from.
+    pub fn options(&self) -> usize {
+        match self.kind {
+            Kind::Choice => self.wrong_answers.len() + 1,
+            Kind::TrueFalse => 2,
+            Kind::MultiSelect => self.answers.len() + self.wrong_answers.len(),
+            Kind::FreeText => 0,
+        }
+    }
+
+    /// Checks that the quiz can be answered with the available keys.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.kind {
+            Kind::Choice => {
+                if self.answer.is_empty() || !self.answers.is_empty() {
+                    return Err("needs exactly one answer".to_string());
+                }
+
+                if self.wrong_answers.is_empty() {
+                    return Err("needs at least one wrong answer".to_string());
+                }
+            }
+
+            Kind::TrueFalse => {
+                if self.answer.parse::<bool>().is_err() {
+                    return Err(format!("answer must be true or false, got {:?}", &*self.answer));
+                }
+
+                if !self.answers.is_empty() || !self.wrong_answers.is_empty() {
+                    return Err("true/false quiz cannot have other answers".to_string());
+                }
+            }
+
+            Kind::MultiSelect => {
+                if self.answers.is_empty() || !self.answer.is_empty() {
+                    return Err("needs a list of answers".to_string());
+                }
+
+                if self.wrong_answers.is_empty() {
+                    return Err("needs at least one wrong answer".to_string());
+                }
+            }
+
+            Kind::FreeText => {
+                if normalize(&self.answer).is_empty() || !self.answers.is_empty() {
+                    return Err("needs exactly one answer".to_string());
+                }
+
+                if !self.wrong_answers.is_empty() {
+                    return Err("free-text quiz cannot have wrong answers".to_string());
+                }
+            }
+        }
+
+        if self.kind != Kind::FreeText && !self.alternatives.is_empty() {
+            return Err("only free-text quizzes can have alternatives".to_string());
+        }
+
+        validate_weight(self.weight)?;
+
+        if self.options() > ANSWER_KEYS.len() {
+            return Err(format!("has {} answers, at most {} are supported", self.options(), ANSWER_KEYS.len()));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn validate_weight(weight: f64) -> Result<(), String> {
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!("weight must be a non-negative number, got {}", weight));
+    }
+
+    Ok(())
+}
+
+/// Lowercases `text` and collapses its whitespace, so typed answers
+/// compare equal regardless of case and spacing.
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn is_one(value: &f64) -> bool {
+    *value == 1.0
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
 }
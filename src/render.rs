@@ -0,0 +1,75 @@
+use std::io::Result;
+use std::str::FromStr;
+
+use crate::grid::Grid;
+use crate::quiz::Mode;
+use crate::panel::CurrentQuiz;
+
+/// The scale used unless `--scale` says otherwise.
+pub const DEFAULT_SCALE: &str = "2x1";
+
+/// Largest number of columns or rows a cell may take.
+const MAX_SCALE: u8 = 8;
+
+/// Columns and rows each cell of the board takes on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale {
+    pub x: u8,
+    pub y: u8,
+}
+
+impl Scale {
+    pub fn new(x: u8, y: u8) -> Scale {
+        Scale {x, y}
+    }
+}
+
+/// Reads `COLUMNSxROWS`, such as `4x2`.
+impl FromStr for Scale {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Scale, String> {
+        let (x, y) = text.split_once('x').ok_or("expected COLUMNSxROWS, such as 2x1")?;
+        let axis = |n: &str| match n.parse() {
+            Ok(n) if (1..=MAX_SCALE).contains(&n) => Ok(n),
+            _ => Err(format!("invalid scale {:?}, expected 1 to {}", n, MAX_SCALE)),
+        };
+
+        Ok(Scale::new(axis(x)?, axis(y)?))
+    }
+}
+
+/// What a renderer gets to see of the game.
+pub struct View<'a> {
+    pub grid: &'a Grid,
+    pub quiz: Option<&'a CurrentQuiz>,
+    pub mode: Mode,
+}
+
+/// Draws the game. `Game` only updates its state and hands a `View` of it
+/// to the renderer once per frame.
+pub trait Renderer {
+    /// Whether the game fits on screen. Only quitting works until it does.
+    fn fits(&self) -> bool;
+
+    /// Draws the whole screen from scratch.
+    fn redraw(&mut self, view: &View) -> Result<()>;
+
+    /// Draws a frame, called every tick.
+    fn draw(&mut self, view: &View) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scale() {
+        assert_eq!("2x1".parse(), Ok(Scale::new(2, 1)));
+        assert_eq!("4x2".parse(), Ok(Scale::new(4, 2)));
+
+        assert!("2".parse::<Scale>().is_err());
+        assert!("0x1".parse::<Scale>().is_err());
+        assert!("2x9".parse::<Scale>().is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::io::{Read, Write, Result};
+use std::{thread, time};
+
+use rand::thread_rng;
+use rand::seq::SliceRandom;
+use termion::{clear, cursor, style};
+
+use crate::locale::Strings;
+use crate::markup;
+use crate::panel::{draw_quiz_panel, CodeStyle, CurrentQuiz};
+use crate::quiz::{Kind, Quiz, Scoring};
+use crate::terminal::{into_screen, Screen, BACKSPACE, PAGE_DOWN, PAGE_UP};
+use crate::text::{self, pad};
+
+/// Widest the question is drawn, however wide the terminal.
+const STUDY_WIDTH: u16 = 60;
+
+/// Narrowest the question is drawn, a narrower terminal is too small.
+const STUDY_MIN_WIDTH: u16 = 20;
+
+/// Rows left for the header, scroll hints, feedback and key help.
+const STUDY_CHROME: u16 = 10;
+
+/// Rows the feedback on an answer may wrap to.
+const FEEDBACK_HEIGHT: u16 = 3;
+
+/// A question answered wrong or partly right, for the summary.
+pub struct Missed {
+    pub question: String,
+    pub answer: String,
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub asked: usize,
+    /// Sum of the credit for each answer, partly right ones count in part.
+    pub score: f64,
+    pub missed: Vec<Missed>,
+}
+
+impl Report {
+    /// Prints the score and the missed questions with their answers.
+    pub fn print(&self, strings: &Strings) {
+        println!("{}: {}/{}", strings.result, score_text(self.score), self.asked);
+
+        if !self.missed.is_empty() {
+            println!();
+            println!("{}", strings.missed);
+        }
+
+        for missed in self.missed.iter() {
+            println!("  {}", missed.question);
+            println!("    → {}", missed.answer);
+        }
+    }
+}
+
+/// Asks quizzes one after another without the game, in random order and
+/// each at most once.
+pub struct Study<R, W: Write> {
+    stdin: R,
+    stdout: W,
+    term_width: u16,
+    term_height: u16,
+
+    quizzes: Vec<Quiz>,
+    code_style: CodeStyle,
+    scoring: Scoring,
+    strings: &'static Strings,
+    report: Report,
+}
+
+impl<R: Read, W: Write> Study<R, W> {
+    /// Asks `count` quizzes, or every quiz with a positive weight if there
+    /// is no count.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(stdin: R, stdout: W, term_width: u16, term_height: u16, quizzes: Vec<Quiz>, count: Option<usize>, code_style: CodeStyle, scoring: Scoring, strings: &'static Strings) -> Study<R, Screen<W>> {
+        let mut quizzes: Vec<Quiz> = quizzes.into_iter().filter(|quiz| quiz.weight > 0.0).collect();
+        quizzes.shuffle(&mut thread_rng());
+        quizzes.truncate(count.unwrap_or(usize::MAX));
+
+        Study {
+            stdin,
+            stdout: into_screen(stdout),
+            term_width,
+            term_height,
+            quizzes,
+            code_style,
+            scoring,
+            strings,
+            report: Report::default(),
+        }
+    }
+
+    /// Runs until every quiz is asked or the player quits.
+    pub fn run(&mut self) -> Result<Report> {
+        write!(self.stdout, "{}{}", style::Reset, cursor::Hide)?;
+
+        let quizzes = std::mem::take(&mut self.quizzes);
+        for (i, quiz) in quizzes.iter().enumerate() {
+            if !self.ask(quiz, i + 1, quizzes.len())? {
+                break;
+            }
+        }
+
+        Ok(std::mem::take(&mut self.report))
+    }
+
+    /// Asks one quiz and waits for the player to move on. Returns false
+    /// if the player quit.
+    fn ask(&mut self, quiz: &Quiz, number: usize, total: usize) -> Result<bool> {
+        let mut current = CurrentQuiz::from_quiz(quiz, self.strings, &mut thread_rng());
+        let mut feedback: Option<f64> = None;
+
+        write!(self.stdout, "{}", clear::All)?;
+        self.draw(&current, feedback, quiz, number, total)?;
+
+        // escape sequences such as PageUp take 4 bytes
+        let mut b: [u8; 4] = [0; 4];
+        loop {
+            thread::sleep(time::Duration::from_millis(50));
+
+            if let Ok(size) = termion::terminal_size() {
+                if size != (self.term_width, self.term_height) {
+                    (self.term_width, self.term_height) = size;
+                    write!(self.stdout, "{}", clear::All)?;
+                    self.draw(&current, feedback, quiz, number, total)?;
+                }
+            }
+
+            if !matches!(self.stdin.read(&mut b), Ok(len) if len > 0) {
+                continue;
+            }
+
+            // only quitting works until the terminal is big enough
+            if self.too_small() {
+                if matches!(b, [b'\x1b' | b'q', 0, 0, 0]) {
+                    return Ok(false);
+                }
+                b = [0; 4];
+                continue;
+            }
+
+            let typing = current.kind == Kind::FreeText && feedback.is_none();
+            let (width, height) = self.panel_size();
+
+            match (b[0], b[1], b[2]) {
+                (b'\x1b', 0, 0) => return Ok(false),
+
+                PAGE_UP => current.scroll_by(-1, width, height),
+                PAGE_DOWN => current.scroll_by(1, width, height),
+
+                (b'q', 0, 0) if !typing => return Ok(false),
+                _ if feedback.is_some() => return Ok(true),
+
+                (key, 0, 0) if current.answer_id(key).is_some() => {
+                    let id = current.answer_id(key).unwrap();
+
+                    if current.kind == Kind::MultiSelect {
+                        current.toggle(id);
+                    } else {
+                        current.selected = vec![id];
+                        feedback = Some(self.judge(&current, quiz));
+                    }
+                }
+                (b'\r', 0, 0) if current.submits_on_enter() => feedback = Some(self.judge(&current, quiz)),
+                (BACKSPACE, 0, 0) if typing => {
+                    current.input.pop();
+                }
+
+                _ if typing && b[0] != b'\x1b' => {
+                    let len = b.iter().position(|&b| b == 0).unwrap_or(b.len());
+                    if let Ok(text) = std::str::from_utf8(&b[..len]) {
+                        current.type_text(text);
+                    }
+                }
+                (b'[', 0, 0) => current.scroll_by(-1, width, height),
+                (b']', 0, 0) => current.scroll_by(1, width, height),
+
+                _ => (),
+            }
+
+            b = [0; 4];
+            self.draw(&current, feedback, quiz, number, total)?;
+        }
+    }
+
+    /// Scores the answer and remembers the quiz if it was not fully right.
+    /// Returns the credit for the answer.
+    fn judge(&mut self, current: &CurrentQuiz, quiz: &Quiz) -> f64 {
+        let credit = current.credit(self.scoring);
+
+        self.report.asked += 1;
+        self.report.score += credit;
+        if credit < 1.0 {
+            self.report.missed.push(Missed {
+                question: markup::plain(&current.question),
+                answer: answer_text(current, quiz),
+            });
+        }
+
+        credit
+    }
+
+    fn panel_size(&self) -> (u16, u16) {
+        let width = self.term_width.saturating_sub(4).min(STUDY_WIDTH);
+        let height = self.term_height.saturating_sub(STUDY_CHROME).max(1);
+
+        (width, height)
+    }
+
+    /// Terminal size needed to draw a quiz.
+    fn min_size(&self) -> (u16, u16) {
+        (STUDY_MIN_WIDTH + 4, STUDY_CHROME + 1)
+    }
+
+    fn too_small(&self) -> bool {
+        let (min_width, min_height) = self.min_size();
+        self.term_width < min_width || self.term_height < min_height
+    }
+
+    fn draw(&mut self, current: &CurrentQuiz, feedback: Option<f64>, quiz: &Quiz, number: usize, total: usize) -> Result<()> {
+        if self.too_small() {
+            return self.draw_too_small();
+        }
+
+        let (width, height) = self.panel_size();
+        let x = (self.term_width - width) / 2 + 1;
+
+        let header = format!("{} {}/{}", self.strings.question, number, total);
+        let score = format!("{}: {}/{}", self.strings.result, score_text(self.report.score), self.report.asked);
+        let header = format!("{}{}", pad(&header, (width as usize).saturating_sub(text::width(&score))), score);
+        write!(self.stdout, "{}{}{}{}", cursor::Goto(x, 1), style::Bold, header, style::Reset)?;
+
+        draw_quiz_panel(&mut self.stdout, current, x, 4, width, height, self.code_style)?;
+
+        let feedback = match feedback {
+            Some(credit) if credit >= 1.0 => format!("✓ {}", self.strings.correct),
+            Some(credit) if credit > 0.0 => format!("~ {}: {}", self.strings.partly_right, answer_text(current, quiz)),
+            Some(_) => format!("✗ {}: {}", self.strings.wrong, answer_text(current, quiz)),
+            None => String::new(),
+        };
+
+        let feedback_y = 4 + height + 2;
+        let mut lines = text::wrap(&[(feedback.as_str(), ())], width).into_iter();
+        for y in feedback_y..feedback_y + FEEDBACK_HEIGHT {
+            let line: String = lines.next().map_or(String::new(), |line| line.into_iter().map(|(text, _)| text).collect());
+            write!(self.stdout, "{}{}", cursor::Goto(x, y), pad(&line, width as usize))?;
+        }
+
+        let help = if feedback.is_empty() {
+            format!("Esc: {}", self.strings.quit)
+        } else {
+            format!("⏎: {}   Esc: {}", self.strings.next, self.strings.quit)
+        };
+        write!(self.stdout, "{}{}", cursor::Goto(x, self.term_height), pad(&help, width as usize))?;
+
+        self.stdout.flush()
+    }
+
+    /// Says how big the terminal needs to be instead of the quiz.
+    fn draw_too_small(&mut self) -> Result<()> {
+        let (width, height) = self.min_size();
+        let message = format!("{} {}x{}", self.strings.too_small, width, height);
+
+        let x = self.term_width.saturating_sub(text::width(&message) as u16) / 2 + 1;
+        write!(self.stdout, "{}{}{}", clear::All, cursor::Goto(x, self.term_height / 2 + 1), message)?;
+
+        self.stdout.flush()
+    }
+}
+
+/// `score` with one decimal if partly right answers made it fractional.
+fn score_text(score: f64) -> String {
+    if score.fract() == 0.0 {
+        format!("{:.0}", score)
+    } else {
+        format!("{:.1}", score)
+    }
+}
+
+/// The correct answer of `quiz` as shown to the player.
+fn answer_text(current: &CurrentQuiz, quiz: &Quiz) -> String {
+    if current.kind == Kind::FreeText {
+        return markup::plain(&quiz.answer);
+    }
+
+    current.correct_answer_ids.iter()
+        .map(|&id| markup::plain(&current.answers[id as usize]))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl<R, W: Write> Drop for Study<R, W> {
+    fn drop(&mut self) {
+        write!(self.stdout, "{}{}", style::Reset, cursor::Show).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{Write, Result};
+use std::{mem, panic, process};
+
+use termion::{clear, color, cursor, style};
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{self, AlternateScreen, IntoAlternateScreen};
+
+use crate::buffer::{Attrs, Buffer};
+use crate::layout::Layout;
+use crate::locale::Strings;
+use crate::panel::CodeStyle;
+use crate::render::{Renderer, Scale, View};
+use crate::text;
+use crate::theme::{Color, Theme};
+
+/// Keys as they are read from the terminal in raw mode.
+pub const BACKSPACE: u8 = 127;
+
+pub const ARROW_UP: (u8, u8, u8) = (27, 91, 65);
+pub const ARROW_DOWN: (u8, u8, u8) = (27, 91, 66);
+pub const ARROW_LEFT: (u8, u8, u8) = (27, 91, 68);
+pub const ARROW_RIGHT: (u8, u8, u8) = (27, 91, 67);
+pub const PAGE_UP: (u8, u8, u8) = (27, 91, 53);
+pub const PAGE_DOWN: (u8, u8, u8) = (27, 91, 54);
+
+/// Draws the game on a terminal with termion escape sequences. Frames are
+/// drawn into `back` and only the cells that differ from `front`, what the
+/// terminal shows, are sent.
+pub struct Terminal<W: Write> {
+    stdout: W,
+    layout: Layout,
+
+    front: Buffer,
+    back: Buffer,
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn new(stdout: W, term_width: u16, term_height: u16, scale: Scale, theme: Theme, code_style: CodeStyle, strings: &'static Strings) -> Terminal<Screen<W>> {
+        Terminal {
+            stdout: into_screen(stdout),
+            layout: Layout::new(term_width, term_height, scale, theme, code_style, strings),
+            front: Buffer::new(term_width, term_height),
+            back: Buffer::new(term_width, term_height),
+        }
+    }
+
+    /// Lays the game out again when the terminal has been resized, and
+    /// returns whether it was.
+    fn check_size(&mut self) -> bool {
+        match termion::terminal_size() {
+            Ok((term_width, term_height)) if (term_width, term_height) != (self.layout.width, self.layout.height) => {
+                self.layout.resize(term_width, term_height);
+                self.front = Buffer::new(term_width, term_height);
+                self.back = Buffer::new(term_width, term_height);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sends the cells of `back` that differ from `front` to the terminal,
+    /// and swaps the two.
+    fn flush(&mut self) -> Result<()> {
+        let mut position = None;
+        let mut current = Attrs::default();
+
+        for (x, y, cell) in self.back.changes(&self.front) {
+            if position != Some((x, y)) {
+                write!(self.stdout, "{}", cursor::Goto(x, y))?;
+            }
+
+            if cell.attrs != current {
+                write_attrs(&mut self.stdout, cell.attrs)?;
+                current = cell.attrs;
+            }
+
+            write!(self.stdout, "{}", cell.symbol)?;
+            position = Some((x + text::width(&cell.symbol) as u16, y));
+        }
+
+        if current != Attrs::default() {
+            write!(self.stdout, "{}", style::Reset)?;
+        }
+
+        mem::swap(&mut self.front, &mut self.back);
+
+        self.stdout.flush()
+    }
+}
+
+impl<W: Write> Renderer for Terminal<W> {
+    fn fits(&self) -> bool {
+        self.layout.fits()
+    }
+
+    /// Clears the screen and draws everything again.
+    fn redraw(&mut self, view: &View) -> Result<()> {
+        write!(self.stdout, "{}{}{}", style::Reset, cursor::Hide, clear::All)?;
+        self.front.clear();
+
+        self.draw(view)
+    }
+
+    fn draw(&mut self, view: &View) -> Result<()> {
+        if self.check_size() {
+            return self.redraw(view);
+        }
+
+        self.back.clear();
+        self.layout.paint(view, &mut self.back);
+
+        self.flush()
+    }
+}
+
+impl<W: Write> Drop for Terminal<W> {
+    fn drop(&mut self) {
+        write!(self.stdout, "{}{}", style::Reset, cursor::Show).unwrap();
+    }
+}
+
+fn write_attrs<W: Write>(stdout: &mut W, attrs: Attrs) -> Result<()> {
+    write!(stdout, "{}", style::Reset)?;
+
+    if attrs.bold {
+        write!(stdout, "{}", style::Bold)?;
+    }
+
+    if attrs.italic {
+        write!(stdout, "{}", style::Italic)?;
+    }
+
+    if attrs.invert {
+        write!(stdout, "{}", style::Invert)?;
+    }
+
+    match attrs.fg {
+        Some(Color::Ansi(value)) => write!(stdout, "{}", color::Fg(color::AnsiValue(value)))?,
+        Some(Color::Rgb(r, g, b)) => write!(stdout, "{}", color::Fg(color::Rgb(r, g, b)))?,
+        None => (),
+    }
+
+    match attrs.bg {
+        Some(Color::Ansi(value)) => write!(stdout, "{}", color::Bg(color::AnsiValue(value)))?,
+        Some(Color::Rgb(r, g, b)) => write!(stdout, "{}", color::Bg(color::Rgb(r, g, b)))?,
+        None => (),
+    }
+
+    Ok(())
+}
+
+/// Size of the terminal, or exits if there is none, such as when output is
+/// piped, since the game and the other full-screen modes need one.
+pub fn terminal_size() -> (u16, u16) {
+    match termion::terminal_size() {
+        Ok(size) => size,
+        Err(err) => {
+            eprintln!("tequiz needs a terminal to play, study or edit: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// The terminal the game and the other full-screen modes draw on: raw
+/// mode on the alternate screen, both undone when it is dropped.
+pub type Screen<W> = RawTerminal<AlternateScreen<W>>;
+
+pub fn into_screen<W: Write>(stdout: W) -> Screen<W> {
+    install_panic_hook();
+    stdout.into_alternate_screen().unwrap().into_raw_mode().unwrap()
+}
+
+/// Puts the terminal back before the default hook prints the panic
+/// message, so it can be read and the shell works afterwards even if the
+/// panic aborts: the main screen and the cursor, written to the tty since
+/// stdout may be redirected, and the settings from before raw mode, kept
+/// with `stty -g`. Must be installed before entering raw mode.
+fn install_panic_hook() {
+    let settings = stty(&["-g"]).ok().filter(|settings| !settings.is_empty());
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        if let Ok(mut tty) = termion::get_tty() {
+            let _ = write!(tty, "{}{}{}", style::Reset, cursor::Show, screen::ToMainScreen);
+            let _ = tty.flush();
+        }
+
+        if let Some(settings) = settings.as_ref() {
+            let _ = stty(&[settings]);
+        }
+
+        previous(info);
+    }));
+}
+
+/// Runs `stty` on the tty, returning what it printed.
+fn stty(args: &[&str]) -> std::result::Result<String, String> {
+    let output = process::Command::new("stty")
+        .args(args)
+        .stdin(termion::get_tty().map_err(|err| err.to_string())?)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,251 @@
+use std::mem;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Columns `text` takes up in the terminal.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// A word and the style of the whitespace before it.
+type Word<'a, S> = (Option<S>, Vec<(&'a str, S)>);
+
+/// Wraps text made of differently styled pieces into lines at most `width`
+/// columns wide, keeping the style of every piece. Words wider than a line
+/// are broken between grapheme clusters. A space between two words takes
+/// the style of the whitespace it replaces.
+pub fn wrap<S: Copy + PartialEq>(pieces: &[(&str, S)], width: u16) -> Vec<Vec<(String, S)>> {
+    let width = width as usize;
+
+    let mut words: Vec<Word<S>> = Vec::new();
+    let mut word = Vec::new();
+    let mut gap = None;
+
+    for &(text, style) in pieces.iter() {
+        for grapheme in text.graphemes(true) {
+            if grapheme.chars().all(char::is_whitespace) {
+                if !word.is_empty() {
+                    words.push((gap.take(), mem::take(&mut word)));
+                }
+
+                gap = gap.or(Some(style));
+            } else {
+                word.push((grapheme, style));
+            }
+        }
+    }
+
+    if !word.is_empty() {
+        words.push((gap, word));
+    }
+
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut line_width = 0;
+
+    for (gap, word) in words {
+        let word_width: usize = word.iter().map(|(grapheme, _)| self::width(grapheme)).sum();
+
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if line_width > 0 {
+            push_piece(&mut line, " ", gap.unwrap_or(word[0].1));
+            line_width += 1;
+        }
+
+        for (grapheme, style) in word {
+            let grapheme_width = self::width(grapheme);
+
+            if line_width > 0 && line_width + grapheme_width > width {
+                lines.push(mem::take(&mut line));
+                line_width = 0;
+            }
+
+            push_piece(&mut line, grapheme, style);
+            line_width += grapheme_width;
+        }
+    }
+
+    if line_width > 0 {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn push_piece<S: PartialEq>(line: &mut Vec<(String, S)>, text: &str, style: S) {
+    match line.last_mut() {
+        Some((last, last_style)) if *last_style == style => last.push_str(text),
+        _ => line.push((text.to_string(), style)),
+    }
+}
+
+/// Cuts `text` down to at most `width` columns, ending with `…` if
+/// anything was cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if self::width(text) <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::with_capacity(width);
+    let mut truncated_width = 0;
+
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = self::width(grapheme);
+        if truncated_width + grapheme_width + 1 > width {
+            break;
+        }
+
+        truncated.push_str(grapheme);
+        truncated_width += grapheme_width;
+    }
+
+    truncated.push('…');
+    truncated
+}
+
+/// Repeats `text` to exactly `width` columns, cutting it off where it no
+/// longer fits and padding with spaces where a wide character would not.
+pub fn fill(text: &str, width: usize) -> String {
+    let mut filled = String::with_capacity(width);
+    let mut filled_width = 0;
+
+    for grapheme in text.graphemes(true).cycle() {
+        let grapheme_width = self::width(grapheme);
+        if grapheme_width == 0 || filled_width + grapheme_width > width {
+            break;
+        }
+
+        filled.push_str(grapheme);
+        filled_width += grapheme_width;
+    }
+
+    filled.push_str(&" ".repeat(width - filled_width));
+    filled
+}
+
+/// Centers `text` in `width` columns, leaning right when it cannot be
+/// centered exactly and `right` is set.
+pub fn center(text: &str, width: usize, right: bool) -> String {
+    let space = width.saturating_sub(self::width(text));
+    let left = if right { space.div_ceil(2) } else { space / 2 };
+
+    pad(&format!("{}{}", " ".repeat(left), text), width)
+}
+
+/// Pads `text` with spaces to `width` columns.
+pub fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(self::width(text))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_into_lines(text: &str, width: u16) -> Vec<String> {
+        wrap(&[(text, ())], width)
+            .into_iter()
+            .map(|line| line.into_iter().map(|(text, _)| text).collect())
+            .collect()
+    }
+
+    fn widths(lines: &[String]) -> Vec<usize> {
+        lines.iter().map(|line| width(line)).collect()
+    }
+
+    #[test]
+    fn wraps_ascii_at_word_boundaries() {
+        let lines = split_into_lines("Which one is NOT one of our acquirers?", 16);
+
+        assert_eq!(lines, vec!["Which one is NOT", "one of our", "acquirers?"]);
+    }
+
+    #[test]
+    fn fills_lines_up_to_width() {
+        let lines = split_into_lines("abcd efgh", 9);
+
+        assert_eq!(lines, vec!["abcd efgh"]);
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        let lines = split_into_lines("  one \t two\nthree  ", 20);
+
+        assert_eq!(lines, vec!["one two three"]);
+    }
+
+    #[test]
+    fn measures_swedish_by_columns_not_bytes() {
+        let lines = split_into_lines("Vilket år köpte PayPal Zettle?", 16);
+
+        assert_eq!(lines, vec!["Vilket år köpte", "PayPal Zettle?"]);
+        assert_eq!(widths(&lines), vec![15, 14]);
+    }
+
+    #[test]
+    fn counts_east_asian_wide_characters_twice() {
+        let lines = split_into_lines("支付 终端 读卡器", 9);
+
+        assert_eq!(lines, vec!["支付 终端", "读卡器"]);
+        assert_eq!(widths(&lines), vec![9, 6]);
+    }
+
+    #[test]
+    fn keeps_combining_marks_with_their_base() {
+        let lines = split_into_lines("cafe\u{301}cafe\u{301}", 4);
+
+        assert_eq!(lines, vec!["cafe\u{301}", "cafe\u{301}"]);
+    }
+
+    #[test]
+    fn hard_breaks_overlong_words() {
+        let lines = split_into_lines("a Supercalifragilistic word", 8);
+
+        assert_eq!(lines, vec!["a", "Supercal", "ifragili", "stic", "word"]);
+    }
+
+    #[test]
+    fn hard_breaks_wide_characters_without_splitting_them() {
+        let lines = split_into_lines("信用卡支付终端", 5);
+
+        assert_eq!(lines, vec!["信用", "卡支", "付终", "端"]);
+    }
+
+    #[test]
+    fn wraps_mixed_script_text_within_width() {
+        let text = "Kortläsare 🧾 card reader カードリーダー med 👍🏽 emoji";
+        let lines = split_into_lines(text, 10);
+
+        assert!(widths(&lines).iter().all(|&width| width <= 10), "{:?}", lines);
+        assert_eq!(lines.join(" ").split_whitespace().collect::<String>(), text.split_whitespace().collect::<String>());
+    }
+
+    #[test]
+    fn empty_text_has_no_lines() {
+        assert!(split_into_lines("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn truncate_keeps_text_that_fits() {
+        assert_eq!(truncate("    let x = 1;", 14), "    let x = 1;");
+    }
+
+    #[test]
+    fn fill_repeats_and_cuts() {
+        assert_eq!(fill("[]", 1), "[");
+        assert_eq!(fill("[]", 2), "[]");
+        assert_eq!(fill("[]", 5), "[][][");
+        assert_eq!(fill("支", 3), "支 ");
+        assert_eq!(fill("", 2), "  ");
+    }
+
+    #[test]
+    fn truncate_marks_cut_text() {
+        assert_eq!(truncate("println!(\"{}\", x);", 10), "println!(…");
+        assert_eq!(truncate("支付终端", 6), "支付…");
+    }
+}
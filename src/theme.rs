@@ -0,0 +1,384 @@
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::{self, Visitor};
+
+use crate::text;
+
+/// The theme used unless `--theme` says otherwise.
+pub const DEFAULT_THEME: &str = "classic";
+
+/// Themes that come with the game besides the default, by name.
+const THEMES: [(&str, &str); 3] = [
+    ("blocks", include_str!("themes/blocks.yaml")),
+    ("ascii", include_str!("themes/ascii.yaml")),
+    ("dots", include_str!("themes/dots.yaml")),
+];
+
+/// Names of the 16 basic terminal colors, in ANSI order.
+const COLOR_NAMES: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "bright_black", "bright_red", "bright_green", "bright_yellow",
+    "bright_blue", "bright_magenta", "bright_cyan", "bright_white",
+];
+
+/// Glyphs `--patterns` gives the seven kinds of tetromino, so they can be
+/// told apart without color.
+const PATTERNS: [&str; 7] = ["[]", "##", "<>", "{}", "()", "%%", "@@"];
+
+/// Piece colors of `--high-contrast`: the saturated bright terminal colors.
+const HIGH_CONTRAST: [Color; 7] = [
+    Color::Ansi(14), Color::Ansi(11), Color::Ansi(9), Color::Ansi(10),
+    Color::Ansi(15), Color::Ansi(12), Color::Ansi(13),
+];
+
+/// Piece colors that stay apart for people who cannot tell some hues
+/// apart: each palette spreads them over the hues that kind of color
+/// blindness still sees, and over lightness.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Palette {
+    /// Red-green, weak green: the Okabe-Ito colors.
+    Deuteranopia,
+
+    /// Red-green, weak red: no dark reds, which look black.
+    Protanopia,
+
+    /// Blue-yellow: reds, cyans and grays.
+    Tritanopia,
+}
+
+impl Palette {
+    fn colors(self) -> [Color; 7] {
+        match self {
+            Palette::Deuteranopia => [
+                Color::Rgb(0x56, 0xb4, 0xe9), Color::Rgb(0xf0, 0xe4, 0x42), Color::Rgb(0xd5, 0x5e, 0x00), Color::Rgb(0x00, 0x9e, 0x73),
+                Color::Rgb(0xe6, 0x9f, 0x00), Color::Rgb(0x00, 0x72, 0xb2), Color::Rgb(0xcc, 0x79, 0xa7),
+            ],
+            Palette::Protanopia => [
+                Color::Rgb(0x56, 0xb4, 0xe9), Color::Rgb(0xf0, 0xe4, 0x42), Color::Rgb(0xff, 0xff, 0xff), Color::Rgb(0x00, 0x72, 0xb2),
+                Color::Rgb(0xe6, 0x9f, 0x00), Color::Rgb(0x99, 0x99, 0x99), Color::Rgb(0xcc, 0x79, 0xa7),
+            ],
+            Palette::Tritanopia => [
+                Color::Rgb(0x00, 0xbf, 0xc4), Color::Rgb(0xff, 0xff, 0xff), Color::Rgb(0xe8, 0x00, 0x0b), Color::Rgb(0x00, 0x7c, 0x80),
+                Color::Rgb(0xff, 0x9f, 0xd0), Color::Rgb(0x8c, 0x8c, 0x8c), Color::Rgb(0x8b, 0x00, 0x00),
+            ],
+        }
+    }
+}
+
+/// A terminal color: a name such as `red` or `bright_blue`, an ANSI color
+/// number up to 255, or `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Color, String> {
+        if let Some(hex) = text.strip_prefix('#') {
+            let channel = |i: usize| hex.get(i..i + 2).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+                _ => Err(format!("invalid color {:?}, expected #rrggbb", text)),
+            };
+        }
+
+        if let Some(i) = COLOR_NAMES.iter().position(|&name| name == text) {
+            return Ok(Color::Ansi(i as u8));
+        }
+
+        text.parse().map(Color::Ansi).map_err(|_| format!("unknown color {:?}", text))
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a color name, a number up to 255 or #rrggbb")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Color, E> {
+        text.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Color, E> {
+        u8::try_from(value).map(Color::Ansi).map_err(|_| E::custom(format!("color {} is out of range, expected up to 255", value)))
+    }
+}
+
+/// Colors of something drawn on screen, the terminal's own if unset.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Paint {
+    #[serde(default)]
+    pub fg: Option<Color>,
+    #[serde(default)]
+    pub bg: Option<Color>,
+}
+
+/// What a cell of the board, a wall or the floor looks like: either just
+/// the text, or `{glyph: "[]", fg: green}` to color it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "GlyphSpec")]
+pub struct Glyph {
+    pub text: String,
+    pub paint: Paint,
+}
+
+impl Glyph {
+    pub fn new(text: &str) -> Glyph {
+        Glyph {
+            text: text.to_string(),
+            paint: Paint::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GlyphSpec {
+    Plain(String),
+    Painted {
+        glyph: String,
+        #[serde(default)]
+        fg: Option<Color>,
+        #[serde(default)]
+        bg: Option<Color>,
+    },
+}
+
+impl From<GlyphSpec> for Glyph {
+    fn from(spec: GlyphSpec) -> Glyph {
+        match spec {
+            GlyphSpec::Plain(text) => Glyph::new(&text),
+            GlyphSpec::Painted { glyph, fg, bg } => Glyph { text: glyph, paint: Paint { fg, bg } },
+        }
+    }
+}
+
+/// How the board and the quiz panel are drawn. Anything a theme file
+/// leaves out is taken from the classic theme.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Settled and falling tetrominoes.
+    pub cell: Glyph,
+
+    /// Colors of the seven kinds of tetromino, used instead of the color
+    /// of `cell` if there are any.
+    pub pieces: Vec<Color>,
+
+    /// Glyphs of the seven kinds of tetromino, used instead of the text of
+    /// `cell` if there are any.
+    pub patterns: Vec<String>,
+
+    pub empty: Glyph,
+
+    /// Where the falling tetromino would land, not shown if unset.
+    pub ghost: Option<Glyph>,
+
+    /// Rows pushed up from below after a wrong answer.
+    pub garbage: Glyph,
+
+    /// Left and right of the board.
+    pub walls: [Glyph; 2],
+
+    /// The two rows below the board.
+    pub floor: [Glyph; 2],
+
+    /// Corners of the boxes drawn over the board, clockwise from the top
+    /// left, and their horizontal edge.
+    pub corners: String,
+    pub edge: String,
+
+    /// Colors of the quiz text.
+    pub panel: Paint,
+}
+
+impl Default for Theme {
+    // the Electronika 60 look
+    fn default() -> Theme {
+        Theme {
+            cell: Glyph::new("[]"),
+            pieces: Vec::new(),
+            patterns: Vec::new(),
+            empty: Glyph::new(" ."),
+            ghost: None,
+            garbage: Glyph::new("[]"),
+            walls: [Glyph::new("<!"), Glyph::new("!>")],
+            floor: [Glyph::new("**"), Glyph::new("\\/")],
+            corners: "╭╮╯╰".to_string(),
+            edge: "─".to_string(),
+            panel: Paint::default(),
+        }
+    }
+}
+
+impl Theme {
+    /// The top and bottom of a box `width` columns wide.
+    pub fn box_edges(&self, width: usize) -> (String, String) {
+        let corners: Vec<char> = self.corners.chars().collect();
+        let edge = self.edge.repeat(width.saturating_sub(2));
+
+        (
+            format!("{}{}{}", corners[0], edge, corners[1]),
+            format!("{}{}{}", corners[3], edge, corners[2]),
+        )
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.pieces = palette.colors().to_vec();
+    }
+
+    /// Gives each kind of tetromino its own glyph.
+    pub fn set_patterns(&mut self) {
+        self.patterns = PATTERNS.iter().map(|pattern| pattern.to_string()).collect();
+    }
+
+    /// Draws the board and the quiz panel bright white on black, with
+    /// the pieces in bright colors.
+    pub fn set_high_contrast(&mut self) {
+        let black = Some(Color::Ansi(0));
+        let paint = Paint { fg: Some(Color::Ansi(15)), bg: black };
+
+        for glyph in [&mut self.cell, &mut self.garbage].into_iter().chain(&mut self.walls).chain(&mut self.floor) {
+            glyph.paint = paint;
+        }
+
+        self.empty.paint = Paint { fg: Some(Color::Ansi(7)), bg: black };
+        if let Some(ghost) = &mut self.ghost {
+            ghost.paint = Paint { fg: Some(Color::Ansi(7)), bg: black };
+        }
+
+        self.pieces = HIGH_CONTRAST.to_vec();
+        self.panel = paint;
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.corners.chars().count() != 4 || text::width(&self.corners) != 4 {
+            return Err(format!("corners must be 4 characters, got {:?}", self.corners));
+        }
+
+        if text::width(&self.edge) != 1 {
+            return Err(format!("edge must be 1 column wide, got {:?}", self.edge));
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads the built-in theme `name`, or else the theme file at that path.
+pub fn load(name: &str) -> Result<Theme, String> {
+    if name == DEFAULT_THEME {
+        return Ok(Theme::default());
+    }
+
+    match THEMES.iter().find(|(theme, _)| *theme == name) {
+        Some((_, source)) => parse(source),
+        None => {
+            let source = fs::read_to_string(name).map_err(|err| format!("{}: not a built-in theme ({}, {}) or a theme file: {}", name, DEFAULT_THEME, names(), err))?;
+            parse(&source).map_err(|err| format!("{}: {}", name, err))
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Theme, String> {
+    let theme: Theme = serde_yaml::from_str(source).map_err(|err| err.to_string())?;
+    theme.validate()?;
+
+    Ok(theme)
+}
+
+fn names() -> String {
+    THEMES.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_built_in_themes() {
+        for (name, _) in THEMES.iter() {
+            assert!(load(name).is_ok(), "{}", name);
+        }
+
+        assert_eq!(load(DEFAULT_THEME), Ok(Theme::default()));
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!("red".parse(), Ok(Color::Ansi(1)));
+        assert_eq!("bright_black".parse(), Ok(Color::Ansi(8)));
+        assert_eq!("208".parse(), Ok(Color::Ansi(208)));
+        assert_eq!("#ff8000".parse(), Ok(Color::Rgb(255, 128, 0)));
+
+        assert!("#ff80".parse::<Color>().is_err());
+        assert!("256".parse::<Color>().is_err());
+        assert!("orange".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn reads_plain_and_painted_glyphs() {
+        let theme = parse("cell: '##'\nempty: {glyph: ' .', fg: 8, bg: '#000000'}\n").unwrap();
+
+        assert_eq!(theme.cell, Glyph::new("##"));
+        assert_eq!(theme.empty.text, " .");
+        assert_eq!(theme.empty.paint, Paint { fg: Some(Color::Ansi(8)), bg: Some(Color::Rgb(0, 0, 0)) });
+    }
+
+    #[test]
+    fn fills_in_missing_fields_from_classic() {
+        let theme = parse("ghost: '::'\n").unwrap();
+
+        assert_eq!(theme.ghost, Some(Glyph::new("::")));
+        assert_eq!(theme.walls, Theme::default().walls);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_bad_corners() {
+        assert!(parse("cells: '[]'\n").is_err());
+        assert!(parse("corners: '++'\n").is_err());
+    }
+
+    #[test]
+    fn palettes_keep_pieces_apart() {
+        for palette in [Palette::Deuteranopia, Palette::Protanopia, Palette::Tritanopia] {
+            let colors = palette.colors();
+
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(color), "{:?} repeats {:?}", palette, color);
+            }
+        }
+    }
+
+    #[test]
+    fn high_contrast_keeps_glyphs() {
+        let mut theme = load("dots").unwrap();
+        theme.set_high_contrast();
+
+        assert_eq!(theme.cell.text, "● ");
+        assert_eq!(theme.walls[0].paint.bg, Some(Color::Ansi(0)));
+        assert_eq!(theme.pieces.len(), 7);
+    }
+
+    #[test]
+    fn draws_box_edges() {
+        assert_eq!(Theme::default().box_edges(6), ("╭────╮".to_string(), "╰────╯".to_string()));
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::grid::{self, Grid};
+
+/// Lines to clear for each `Strategy::LevelUp` quiz, like a level of the
+/// original game. The game itself stays at level 0.
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// When quizzes pop up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Strategy {
+    /// Roll `--quiz-ratio` for every new piece.
+    #[default]
+    Piece,
+
+    /// Every `--every` pieces.
+    EveryN,
+
+    /// Whenever lines are cleared.
+    LineClear,
+
+    /// Every `LINES_PER_LEVEL` lines cleared.
+    LevelUp,
+
+    /// Whenever four lines are cleared at once.
+    Tetris,
+
+    /// Every `--interval` seconds of play.
+    Interval,
+
+    /// Roll for every new piece, up to twice `--quiz-ratio` on an empty
+    /// board and less the higher the stack gets.
+    Adaptive,
+}
+
+pub struct Trigger {
+    strategy: Strategy,
+    ratio: u8,
+    every: u32,
+    interval: Duration,
+
+    pieces: u32,
+    levels: u32,
+    timer: Duration,
+}
+
+impl Trigger {
+    pub fn new(strategy: Strategy, ratio: u8, every: u32, interval: Duration) -> Trigger {
+        Trigger {
+            strategy,
+            ratio,
+            every: every.max(1),
+            interval,
+            pieces: 0,
+            levels: 0,
+            timer: Duration::ZERO,
+        }
+    }
+
+    /// Whether a quiz should pop up, given what happened on `grid` during
+    /// the last `elapsed`. Only called while no quiz is open.
+    pub fn fire(&mut self, grid: &Grid, elapsed: Duration, rng: &mut impl Rng) -> bool {
+        match self.strategy {
+            Strategy::Piece => grid.on_new_tetromino && roll(rng, self.ratio as u32),
+
+            Strategy::EveryN => {
+                if grid.on_new_tetromino {
+                    self.pieces += 1;
+                }
+
+                if self.pieces >= self.every {
+                    self.pieces = 0;
+                    return true;
+                }
+
+                false
+            }
+
+            Strategy::LineClear => grid.on_lines_cleared > 0,
+            Strategy::LevelUp => {
+                let levels = grid.cleared / LINES_PER_LEVEL;
+                let level_up = levels > self.levels;
+
+                // also catches up after the board is reset
+                self.levels = levels;
+                level_up
+            }
+            Strategy::Tetris => grid.on_lines_cleared >= 4,
+
+            Strategy::Interval => {
+                self.timer += elapsed;
+
+                if self.timer >= self.interval {
+                    self.timer = Duration::ZERO;
+                    return true;
+                }
+
+                false
+            }
+
+            Strategy::Adaptive => {
+                let free = (grid::HEIGHT - grid.stack_height()) as u32;
+                let ratio = self.ratio as u32 * 2 * free / grid::HEIGHT as u32;
+
+                grid.on_new_tetromino && roll(rng, ratio.min(100))
+            }
+        }
+    }
+}
+
+fn roll(rng: &mut impl Rng, ratio: u32) -> bool {
+    rng.gen_ratio(ratio, 100)
+}